@@ -0,0 +1,73 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use demo_game::game_object::broadphase::{scatter, Broadphase};
+use demo_game::game_object::BoundInfo;
+use demo_game::math::bounds::Bounds;
+use demo_game::util::Random;
+
+const COUNTS: [usize; 4] = [1_000, 2_000, 4_000, 8_000];
+
+/// Objects at a constant density, like a level that grows with its objects
+fn objects(count: usize) -> Vec<BoundInfo> {
+	scatter(count, (count as f32).sqrt() * 50.0, &mut Random::new(1))
+}
+
+fn bench_overlaps(c: &mut Criterion) {
+	let mut group = c.benchmark_group("overlaps");
+
+	for count in COUNTS {
+		let objects = objects(count);
+
+		group.bench_with_input(BenchmarkId::new("brute_force", count), &objects, |b, objects| {
+			b.iter(|| {
+				objects
+					.iter()
+					.map(|a| objects.iter().filter(|b| b.bounds.intersects(a.bounds)).count())
+					.sum::<usize>()
+			})
+		});
+
+		group.bench_with_input(BenchmarkId::new("grid", count), &objects, |b, objects| {
+			b.iter(|| {
+				let broadphase = Broadphase::new(128.0, black_box(objects.clone()));
+
+				objects.iter().map(|a| broadphase.query_rect(a.bounds).len()).sum::<usize>()
+			})
+		});
+	}
+
+	group.finish();
+}
+
+/// A tick where every tenth object moves, like the dynamic bodies of a level
+fn bench_tick(c: &mut Criterion) {
+	let mut group = c.benchmark_group("tick");
+
+	for count in COUNTS {
+		let objects = objects(count);
+		let mut moved = objects.clone();
+
+		for object in moved.iter_mut().step_by(10) {
+			object.bounds.x += 200.0;
+		}
+
+		group.bench_with_input(BenchmarkId::new("rebuild", count), &moved, |b, moved| {
+			b.iter(|| Broadphase::new(128.0, black_box(moved.clone())))
+		});
+
+		group.bench_with_input(BenchmarkId::new("update", count), &moved, |b, moved| {
+			b.iter_batched(
+				|| Broadphase::new(128.0, objects.clone()),
+				|mut broadphase| {
+					broadphase.update(black_box(moved.clone()));
+					broadphase
+				},
+				criterion::BatchSize::LargeInput,
+			)
+		});
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, bench_overlaps, bench_tick);
+criterion_main!(benches);
@@ -5,6 +5,7 @@ pub enum Action {
 	Quit,
 	Debug,
 	FpsLimit,
+	Reload,
 	MoveLeft,
 	MoveRight,
 	MoveUp,
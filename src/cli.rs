@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use crate::errors::ArgumentError;
+
+static USAGE: &str = "usage: demo-game [--headless <ticks>] [--level <index>] [--script <file>] \
+	[--output <file>] [--record <file>] [--replay <file>] [--seed <number>]";
+
+#[derive(Debug, Default, Clone)]
+pub struct Arguments {
+	/// run the given number of simulation ticks without a window
+	pub headless_ticks: Option<u64>,
+	/// index of the level to load
+	pub level: usize,
+	/// action script to feed into a headless run
+	pub script: Option<PathBuf>,
+	/// where a headless run writes the final game objects; stdout if not set
+	pub output: Option<PathBuf>,
+	/// file the input of a windowed run gets recorded to
+	pub record: Option<PathBuf>,
+	/// recording to replay headless and compare against
+	pub replay: Option<PathBuf>,
+	/// seed of the random generator; taken from the current time if not set
+	pub seed: Option<u64>,
+}
+
+impl Arguments {
+	pub fn parse<I>(mut args: I) -> Result<Self, ArgumentError>
+	where
+		I: Iterator<Item = String>,
+	{
+		let mut arguments = Self::default();
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--headless" => arguments.headless_ticks = Some(parse_value(&arg, args.next())?),
+				"--level" => arguments.level = parse_value(&arg, args.next())?,
+				"--script" => arguments.script = Some(parse_value(&arg, args.next())?),
+				"--output" => arguments.output = Some(parse_value(&arg, args.next())?),
+				"--record" => arguments.record = Some(parse_value(&arg, args.next())?),
+				"--replay" => arguments.replay = Some(parse_value(&arg, args.next())?),
+				"--seed" => arguments.seed = Some(parse_value(&arg, args.next())?),
+				_ => {
+					return Err(ArgumentError {
+						argument: arg,
+						reason: String::from(USAGE),
+					})
+				}
+			}
+		}
+
+		Ok(arguments)
+	}
+}
+
+fn parse_value<T: FromStr>(arg: &str, value: Option<String>) -> Result<T, ArgumentError> {
+	let Some(value) = value else {
+		return Err(ArgumentError {
+			argument: arg.to_owned(),
+			reason: String::from("missing value"),
+		});
+	};
+
+	value.parse().map_err(|_| ArgumentError {
+		argument: arg.to_owned(),
+		reason: format!("could not parse value '{value}'"),
+	})
+}
@@ -1,17 +1,70 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
+use crate::serialization::AssetId;
 
 #[derive(Debug)]
 pub struct DataLoadError {
-	pub path: PathBuf
+	pub path: PathBuf,
+	pub reason: String,
 }
 
 impl Display for DataLoadError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		let path = self.path.display();
-		write!(f, "unable to read configuration at {path}")
+		let reason = &self.reason;
+		write!(f, "unable to read configuration at {path}: {reason}")
 	}
 }
 
 impl Error for DataLoadError {}
+
+#[derive(Debug)]
+pub struct ArgumentError {
+	pub argument: String,
+	pub reason: String,
+}
+
+impl Display for ArgumentError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let argument = &self.argument;
+		let reason = &self.reason;
+		write!(f, "invalid argument {argument}: {reason}")
+	}
+}
+
+impl Error for ArgumentError {}
+
+#[derive(Debug)]
+pub enum LayoutError {
+	/// a constraint refers to an element that is neither the parent nor a sibling
+	MissingElement { id: AssetId, other_id: AssetId },
+	/// an axis is not fully defined by the constraints
+	Underdetermined { id: AssetId },
+	/// constraints define an axis more than once with different values
+	Conflicting { id: AssetId },
+	/// the resolved frame violates a constraint
+	Unsatisfiable { id: AssetId },
+	/// the elements depend on each other
+	Cycle { ids: Vec<AssetId> },
+}
+
+impl Display for LayoutError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			LayoutError::MissingElement { id, other_id } => {
+				write!(f, "element {id} is constrained to unknown element {other_id}")
+			}
+			LayoutError::Underdetermined { id } => {
+				write!(f, "constraints of element {id} do not define its frame")
+			}
+			LayoutError::Conflicting { id } => write!(f, "constraints of element {id} conflict"),
+			LayoutError::Unsatisfiable { id } => {
+				write!(f, "constraints of element {id} can not be satisfied")
+			}
+			LayoutError::Cycle { ids } => write!(f, "elements {ids:?} are constrained to each other"),
+		}
+	}
+}
+
+impl Error for LayoutError {}
@@ -1,27 +1,36 @@
-use std::collections::HashSet;
 use crate::game_object::world::World;
-use crate::gui::{Align, ElementType, TextFormat, UiElement};
+use crate::game_object::drawable::CoordinateSpace;
+use crate::gui::{Align, ElementType, Handler, TextFormat, UiElement};
 use crate::math::bounds::Bounds;
 use sdl3::Sdl;
-use sdl3::event::Event;
+use sdl3::event::{Event, WindowEvent};
+use sdl3::keyboard::Keycode;
 use sdl3::mouse::MouseButton;
-use sdl3::pixels::{Color, PixelFormat, PixelFormatEnum};
-use sdl3::render::{FPoint, FRect, SurfaceCanvas, TextureCreator, WindowCanvas};
-use sdl3::surface::{Surface, SurfaceContext};
+use sdl3::pixels::Color;
+use sdl3::render::{BlendMode, FPoint, FRect, TextureCreator, WindowCanvas};
 use sdl3::timer::performance_frequency;
 use sdl3::ttf::{Sdl3TtfContext};
 use sdl3::video::WindowContext;
+use std::path::{Path, PathBuf};
 use std::thread::sleep;
 use std::time::Duration;
 use crate::actions::Action;
 use crate::game_assets::GameAssets;
-use crate::mouse::{Mouse, MouseButtonState};
-use crate::settings::{Settings};
+use crate::gamepad::Gamepads;
+use crate::input::{InputAxis, InputSource, InputState};
+use crate::mouse::MouseButtonState;
+use crate::render_batch::RenderBatch;
+use crate::replay::Recording;
+use crate::settings::{Settings, SETTINGS_PATH};
 
 type MilliSeconds = u64;
 
 static WINDOW_TITLE: &str = "rust-sdl3 demo";
 
+/// Upper limit for the time a single frame may add to the simulation; prevents the simulation
+/// from spiraling when a frame takes longer than the steps it has to catch up on
+static MAX_FRAME_DELTA: f64 = 0.25;
+
 #[derive(Debug, Copy, Clone, Default)]
 struct FrameData {
 	pub last_tick: u64,
@@ -30,6 +39,8 @@ struct FrameData {
 	pub fps_frame_count: u64,
 	pub fps_last_tick: u64,
 	pub fps: f32,
+	pub tick_number: u64,
+	pub accumulator: f64,
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -37,64 +48,68 @@ struct SystemState {
 	should_quit: bool,
 	should_show_debug: bool,
 	menu_open: bool,
+	rebinding: Option<Action>,
 }
 
 pub struct Game<'a> {
-    actions: HashSet<Action>,
+    input: InputState,
     sdl_context: &'a Sdl,
-    main_texture_creator: TextureCreator<WindowContext>,
-    menu_texture_creator: TextureCreator<SurfaceContext<'a>>,
     main_canvas: WindowCanvas,
-    menu_canvas: SurfaceCanvas<'a>,
     world: World,
 	performance_frequency: f64,
 	settings: Settings,
 	assets: GameAssets<'a>,
 	frame_data: FrameData,
     window_bounds: FRect,
-	mouse: Mouse,
+	gamepads: Gamepads,
 	system_state: SystemState,
-	min_frame_time: MilliSeconds
+	min_frame_time: MilliSeconds,
+	fixed_delta_t: f64,
+	recording: Option<Recording>,
+	recording_path: Option<PathBuf>,
 }
 
 impl<'a> Game<'a> {
-    pub fn new(
-        settings: Settings,
-        sdl_context: &'a Sdl,
-        ttf_context: &'a Sdl3TtfContext,
-    ) -> Self {
-		let assets = GameAssets::new(&settings.asset_file, ttf_context);
-
+    /// Opens the game window; the texture creator of the returned canvas has to outlive the game
+    pub fn create_canvas(settings: &Settings, sdl_context: &Sdl) -> WindowCanvas {
         let video_subsystem = sdl_context.video().unwrap();
 
         let window = video_subsystem
             .window(WINDOW_TITLE, settings.width as u32, settings.height as u32)
             .position_centered()
+            .resizable()
             .build()
             .unwrap();
 
-        let canvas = window.into_canvas();
-
-        let menu_canvas = SurfaceCanvas::from_surface(
-            Surface::new(settings.width as u32, settings.height as u32, PixelFormat::from(PixelFormatEnum::ARGB8888))
-                .expect("Surface creation error"),
-        )
-        .expect("Surface creation error");
+        window.into_canvas()
+    }
 
-        let main_texture_creator = canvas.texture_creator();
+    pub fn new(
+        settings: Settings,
+        sdl_context: &'a Sdl,
+        ttf_context: &'a Sdl3TtfContext,
+        mut canvas: WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        seed: u64,
+    ) -> Self {
+		let assets = GameAssets::new(
+			&settings.asset_file,
+			&settings.keymap,
+			&settings.gamepad_map,
+			ttf_context,
+			texture_creator,
+		);
 
-        let menu_texture_creator = menu_canvas.texture_creator();
+        canvas.set_blend_mode(BlendMode::Blend);
 
 		let min_frame_time: MilliSeconds =  1_000u64 / settings.frame_limit as u64;
+		let fixed_delta_t = 1.0 / settings.tick_rate as f64;
 
         Self {
-            actions: HashSet::new(),
-            main_texture_creator,
-            menu_texture_creator,
-            world: World::new(settings.width as f32, settings.height as f32),
+            input: InputState::default(),
+            world: World::new(settings.width as f32, settings.height as f32, seed),
             sdl_context,
             main_canvas: canvas,
-            menu_canvas,
             performance_frequency: performance_frequency() as f64,
 			frame_data: FrameData::default(),
             window_bounds: FRect {
@@ -105,13 +120,17 @@ impl<'a> Game<'a> {
             },
 			settings,
 			assets,
-			mouse: Mouse::default(),
+			gamepads: Gamepads::new(sdl_context.gamepad().ok()),
 			system_state: SystemState {
 				should_quit: false,
 				should_show_debug: false,
 				menu_open: false,
+				rebinding: None,
 			},
-			min_frame_time
+			min_frame_time,
+			fixed_delta_t,
+			recording: None,
+			recording_path: None,
         }
     }
 
@@ -127,6 +146,25 @@ impl<'a> Game<'a> {
             .expect("setting window title failed");
 
         self.world.load_level(level);
+
+        self.assets
+            .layout_gui(self.window_bounds)
+            .unwrap_or_else(|err| panic!("gui layout error: {err}"));
+    }
+
+    /// Records the input of every tick, the recording is saved when the game quits or reloads
+    pub fn start_recording(&mut self, path: PathBuf) {
+        self.recording = Some(Recording::new(self.world.seed(), 0, self.settings.tick_rate));
+        self.recording_path = Some(path);
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take()
+            && let Some(path) = self.recording_path.take()
+            && let Err(err) = recording.save(&path)
+        {
+            eprintln!("{err}");
+        }
     }
 
     pub fn run(&mut self) {
@@ -147,10 +185,12 @@ impl<'a> Game<'a> {
 
             self.tick();
         }
+
+        self.stop_recording();
     }
 
     fn register_events(&mut self, event: Event) {
-		self.mouse.buttons = MouseButtonState::NONE;
+		self.input.mouse.buttons = MouseButtonState::NONE;
 
         match event {
             Event::KeyDown {
@@ -158,8 +198,10 @@ impl<'a> Game<'a> {
                 repeat: false,
                 ..
             } => {
-                if let Some(action) = self.assets.keymap.get(&keycode) {
-                    self.actions.insert(*action);
+                if let Some(action) = self.system_state.rebinding {
+                    self.rebind(keycode, action);
+                } else if let Some(action) = self.assets.keymap.get(&keycode) {
+                    self.input.press(InputSource::Keyboard, *action);
                 }
             }
             Event::KeyUp {
@@ -167,17 +209,23 @@ impl<'a> Game<'a> {
                 ..
             } => {
                 if let Some(action) = self.assets.keymap.get(&keycode) {
-                    self.actions.remove(action);
+                    self.input.release(InputSource::Keyboard, *action);
                 }
             }
 			Event::MouseMotion { x, y, .. } => {
-				self.mouse.pos = FPoint { x, y }
+				self.input.mouse.pos = FPoint { x, y };
+
+				let aim_x = x / self.window_bounds.w * 2.0 - 1.0;
+				let aim_y = y / self.window_bounds.h * 2.0 - 1.0;
+
+				self.input.set_axis(InputAxis::AimX, aim_x, 0.0);
+				self.input.set_axis(InputAxis::AimY, aim_y, 0.0);
 			}
             Event::MouseButtonDown {
                 mouse_btn,
                 ..
             } => {
-                self.mouse.buttons |= match mouse_btn {
+                self.input.mouse.buttons |= match mouse_btn {
 					MouseButton::Left => MouseButtonState::LEFT_BUTTON,
 					MouseButton::Middle => MouseButtonState::MIDDLE_BUTTON,
 					MouseButton::Right => MouseButtonState::RIGHT_BUTTON,
@@ -188,39 +236,66 @@ impl<'a> Game<'a> {
                 mouse_btn,
                 ..
             } => {
-				self.mouse.buttons &= match mouse_btn {
+				self.input.mouse.buttons &= match mouse_btn {
 					MouseButton::Left => MouseButtonState::LEFT_BUTTON,
 					MouseButton::Middle => MouseButtonState::MIDDLE_BUTTON,
 					MouseButton::Right => MouseButtonState::RIGHT_BUTTON,
 					_ => MouseButtonState::NONE,
 				}.not()
             }
+            Event::Window {
+                win_event: WindowEvent::Resized(w, h),
+                ..
+            } => {
+                self.window_bounds.w = w as f32;
+                self.window_bounds.h = h as f32;
+                self.world.set_viewport(w as f32, h as f32);
+
+                if let Err(err) = self.assets.layout_gui(self.window_bounds) {
+                    eprintln!("gui layout error: {err}");
+                }
+            }
             Event::Quit { .. } => {
-				self.actions.insert(Action::Quit);
+				self.input.actions.insert(Action::Quit);
+            }
+            event => {
+                self.gamepads.handle_event(
+                    &event,
+                    &self.assets.gamepad_map,
+                    &mut self.input,
+                    self.settings.dead_zone,
+                );
             }
-            _ => {}
         }
     }
 
     fn handle_system_events(&mut self) {
-        if self.actions.contains(&Action::Quit) {
+        if self.input.actions.contains(&Action::Quit) {
             self.system_state.should_quit = true;
             return;
         }
 
-        if self.actions.contains(&Action::Menu) {
-			self.actions.remove(&Action::Menu);
+        if self.input.actions.contains(&Action::Menu) {
+			self.input.actions.remove(&Action::Menu);
             self.system_state.menu_open = !self.system_state.menu_open;
             self.frame_data.last_tick = 0;
         }
 
-        if self.actions.contains(&Action::FpsLimit) {
-			self.actions.remove(&Action::FpsLimit);
+        if self.input.actions.contains(&Action::FpsLimit) {
+			self.input.actions.remove(&Action::FpsLimit);
             self.settings.frame_limit_active = !self.settings.frame_limit_active
         }
 
-        if self.actions.contains(&Action::Debug) {
-			self.actions.remove(&Action::Debug);
+        if self.input.actions.contains(&Action::Reload) {
+			self.input.actions.remove(&Action::Reload);
+            // a replay only covers a single load of the level, so the recording ends here
+            self.stop_recording();
+            self.assets.reload();
+            self.init();
+        }
+
+        if self.input.actions.contains(&Action::Debug) {
+			self.input.actions.remove(&Action::Debug);
             self.system_state.should_show_debug = !self.system_state.should_show_debug
         }
 
@@ -236,51 +311,90 @@ impl<'a> Game<'a> {
 
 		let main_menu = &mut self.assets.gui_data[0];
 
-		let Some(action) = main_menu.handle_event(self.mouse) else {
+		match main_menu.handle_event(self.input.mouse) {
+			Some(Handler::Action(action)) => {
+				self.input.actions.insert(action);
+			}
+			Some(Handler::Rebind(action)) => {
+				self.system_state.rebinding = Some(action);
+			}
+			_ => {}
+		}
+	}
+
+	/// Binds the key pressed while waiting for a rebind and saves it to the settings file;
+	/// escape cancels unless the menu itself is rebound
+	fn rebind(&mut self, keycode: Keycode, action: Action) {
+		self.system_state.rebinding = None;
+
+		if keycode == Keycode::Escape && action != Action::Menu {
 			return;
-		};
+		}
 
-		self.actions.insert(action);
-	}
+		self.settings.rebind(keycode, action);
+		self.assets.update_keymap(&self.settings.keymap);
 
-    fn render_drawables(&mut self) {
-        let drawables = self.world.get_drawables();
+		if let Err(err) = self.settings.save_keymap(Path::new(SETTINGS_PATH)) {
+			eprintln!("{err}");
+		}
+	}
 
-        for (rect, drawable) in drawables {
-            if let Some(texture_index) = drawable.texture_id
-                && let Some(surface) = self.assets.surfaces.get(&texture_index)
+    fn render_drawables(&mut self, alpha: f32) {
+        let camera = *self.world.camera();
+        let drawables = self.world.get_drawables(alpha);
+        let mut batch = RenderBatch::default();
+        let mut outlines = Vec::new();
+
+        for (bounds, drawable) in drawables {
+            let rect = match drawable.space {
+                CoordinateSpace::World => camera.project(bounds, alpha),
+                CoordinateSpace::Screen => bounds,
+            };
+
+            let (texture_id, source) = match drawable.sprite.and_then(|s| self.assets.sprite_frame(s)) {
+                Some((texture_id, source)) => (Some(texture_id), Some(source)),
+                None => (drawable.texture_override.or(drawable.texture_id), None),
+            };
+
+            if let Some(texture_index) = texture_id
+                && let Some(texture) = self.assets.texture(texture_index)
             {
-                if let Ok(mut texture) = self
-                    .main_texture_creator
-                    .create_texture_from_surface(surface)
-                {
-                    if let Some(color) = drawable.color
-                        && drawable.tint_texture
-                    {
-                        texture.set_color_mod(color.r, color.g, color.b);
-                    }
-
-                    self.main_canvas
-                        .copy(&texture, None, rect)
-                        .expect("texture error");
+                // drawables that follow each other with the same texture are drawn together
+                if batch.texture_id() != Some(texture_index) {
+                    batch.flush(&mut self.main_canvas, &self.assets);
                 }
+
+                let tint = match drawable.color {
+                    Some(color) if drawable.tint_texture => color,
+                    _ => Color::WHITE,
+                };
+
+                batch.push(texture_index, (texture.width() as f32, texture.height() as f32), source, rect, tint);
             } else if let Some(color) = drawable.color {
+                batch.flush(&mut self.main_canvas, &self.assets);
+
                 self.main_canvas.set_draw_color(color);
                 self.main_canvas.fill_rect(rect).expect("draw error");
             }
 
             if self.system_state.should_show_debug {
-                self.main_canvas.set_draw_color(Color::MAGENTA);
-                self.main_canvas
-                    .draw_rect(FRect {
-                        x: rect.x - 1.0,
-                        y: rect.y - 1.0,
-                        w: rect.w + 2.0,
-                        h: rect.h + 2.0,
-                    })
-                    .expect("draw error");
+                outlines.push(FRect {
+                    x: rect.x - 1.0,
+                    y: rect.y - 1.0,
+                    w: rect.w + 2.0,
+                    h: rect.h + 2.0,
+                });
             }
         }
+
+        batch.flush(&mut self.main_canvas, &self.assets);
+
+        // on top of everything, so that batches don't cover them
+        self.main_canvas.set_draw_color(Color::MAGENTA);
+
+        for outline in outlines {
+            self.main_canvas.draw_rect(outline).expect("draw error");
+        }
     }
 
     fn render_ui_element(&mut self, element: &UiElement, parent_bounds: FRect) {
@@ -292,43 +406,38 @@ impl<'a> Game<'a> {
 		bounds.x += parent_bounds.x;
 		bounds.y += parent_bounds.y;
 
-		self.menu_canvas.set_draw_color(element.bg);
-		self.menu_canvas
+		self.main_canvas.set_draw_color(element.bg);
+		self.main_canvas
 			.fill_rect(bounds)
 			.expect("Failed to fill rect");
 
         match &element.element_type {
 			ElementType::Label { text, format } => {
-                let surface = self.build_text_surface(text, format);
-                let surface_width = surface.width() as f32;
-                let surface_height = surface.height() as f32;
-
-                let texture = self
-                    .menu_texture_creator
-                    .create_texture_from_surface(surface)
-                    .expect("texture creation panic");
+                let texture = self.assets.text_texture(text, format);
+                let texture_width = texture.width() as f32;
+                let texture_height = texture.height() as f32;
 
                 let x = match format.justify {
                     Align::Start => 0.0,
-                    Align::Center => (bounds.w - surface_width) / 2.0,
-                    Align::End => bounds.w - surface_width,
+                    Align::Center => (bounds.w - texture_width) / 2.0,
+                    Align::End => bounds.w - texture_width,
                 } + bounds.x;
 
                 let y = match format.align {
                     Align::Start => 0.0,
-                    Align::Center => (bounds.h - surface_height) / 2.0,
-                    Align::End => bounds.h - surface_height,
+                    Align::Center => (bounds.h - texture_height) / 2.0,
+                    Align::End => bounds.h - texture_height,
                 } + bounds.y;
 
                 let text_rect = FRect {
                     x,
                     y,
-                    w: surface_width,
-                    h: surface_height,
+                    w: texture_width,
+                    h: texture_height,
                 };
 
-                self.menu_canvas
-                    .copy(&texture, None, Some(text_rect))
+                self.main_canvas
+                    .copy(texture, None, Some(text_rect))
                     .expect("debug message panic");
             }
 			_ => {}
@@ -344,27 +453,11 @@ impl<'a> Game<'a> {
     }
 
     fn render_menu(&mut self) {
-        self.menu_canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
-        self.menu_canvas.clear();
-
         if let Some(main_menu) = self.assets.gui_data.get(0) {
             let e = main_menu.clone();
 
             self.render_ui_element(&e, self.window_bounds);
         }
-
-        self.menu_canvas.present();
-
-        let menu_surface = self.menu_canvas.surface();
-
-        let menu_surface = self
-            .main_texture_creator
-            .create_texture_from_surface(menu_surface)
-            .expect("texture creation panic");
-
-        self.main_canvas
-            .copy(&menu_surface, None, Some(self.window_bounds))
-            .expect("menu render error");
     }
 
     fn tick(&mut self) {
@@ -383,10 +476,24 @@ impl<'a> Game<'a> {
         self.main_canvas.clear();
 
         if !self.system_state.menu_open {
-            self.world.tick(delta_t_sec, &self.actions);
+            self.frame_data.accumulator += f64::min(delta_t_sec, MAX_FRAME_DELTA);
+
+            // the world always advances in steps of the same size, independent of the frame rate
+            while self.frame_data.accumulator >= self.fixed_delta_t {
+                self.world.tick(self.fixed_delta_t, &self.input);
+
+                if let Some(recording) = &mut self.recording {
+                    recording.record(&self.input, self.world.get_game_objects());
+                }
+
+                self.frame_data.accumulator -= self.fixed_delta_t;
+                self.frame_data.tick_number += 1;
+            }
         }
 
-        self.render_drawables();
+        let alpha = self.frame_data.accumulator / self.fixed_delta_t;
+
+        self.render_drawables(alpha as f32);
 
         if self.system_state.menu_open {
             self.render_menu();
@@ -397,6 +504,7 @@ impl<'a> Game<'a> {
         }
 
         self.main_canvas.present();
+        self.assets.texture_cache.end_frame();
 
         self.frame_data.last_tick = now;
         self.frame_data.frame_number += 1;
@@ -424,21 +532,7 @@ impl<'a> Game<'a> {
         }
     }
 
-    fn build_text_surface(&self, text: &String, format: &TextFormat) -> Surface<'_> {
-        let font = self
-			.assets
-            .fonts
-            .get(&format.font_id)
-            .expect("Invalid debug font id");
-        let rendered_text = font.render(text);
-        let surface = rendered_text
-            .blended(format.color)
-            .expect("text render panic");
-
-        surface
-    }
-
-    fn render_msg(&mut self, msg: &String, pos: FPoint) -> FRect {
+    fn render_msg(&mut self, msg: &str, pos: FPoint) -> FRect {
 		let format = TextFormat {
 			font_id: self.assets.game_data.debug_font_id,
 			color: Color::WHITE,
@@ -446,26 +540,21 @@ impl<'a> Game<'a> {
 			align: Align::Start,
 		};
 
-        let surface = self.build_text_surface(msg, &format);
+        let texture = self.assets.text_texture(msg, &format);
 
-        let surface_width = surface.width() as f32;
-        let surface_height = surface.height() as f32;
+        let texture_width = texture.width() as f32;
+        let texture_height = texture.height() as f32;
 
-        let texture = self
-            .main_texture_creator
-            .create_texture_from_surface(surface)
-            .expect("texture creation panic");
+        let text_rect = FRect::new(pos.x + 5.0, pos.y + 2.0, texture_width, texture_height);
 
-        let text_rect = FRect::new(pos.x + 5.0, pos.y + 2.0, surface_width, surface_height);
-
-        let bg_rect = FRect::new(pos.x, pos.y, surface_width + 10.0, surface_height + 4.0);
+        let bg_rect = FRect::new(pos.x, pos.y, texture_width + 10.0, texture_height + 4.0);
 
         self.main_canvas.set_draw_color(Color::BLACK);
         self.main_canvas
             .fill_rect(bg_rect)
             .expect("debug message panic");
         self.main_canvas
-            .copy(&texture, None, Some(text_rect))
+            .copy(texture, None, Some(text_rect))
             .expect("debug message panic");
 
         bg_rect
@@ -480,8 +569,11 @@ impl<'a> Game<'a> {
         let fn_text = format!("frame_count: {}", self.frame_data.frame_number);
         let fn_rect = self.render_msg(&fn_text, FPoint::new(dt_rect.x, dt_rect.bottom()));
 
+        let tn_text = format!("tick_count: {}", self.frame_data.tick_number);
+        let tn_rect = self.render_msg(&tn_text, FPoint::new(fn_rect.x, fn_rect.bottom()));
+
         let ft_text = format!("frame_time: {0:.2}ms", self.frame_data.frame_time);
-        let ft_rect = self.render_msg(&ft_text, FPoint::new(fn_rect.x, fn_rect.bottom()));
+        let ft_rect = self.render_msg(&ft_text, FPoint::new(tn_rect.x, tn_rect.bottom()));
 
         let fw_text = format!("fps_limit: {}", self.settings.frame_limit_active);
         let fw_rect = self.render_msg(&fw_text, FPoint::new(ft_rect.x, ft_rect.bottom()));
@@ -2,29 +2,48 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
+use sdl3::gamepad::Button;
 use sdl3::keyboard::Keycode;
+use sdl3::render::{FRect, Texture, TextureCreator};
 use sdl3::surface::Surface;
 use sdl3::ttf::{Font, Sdl3TtfContext};
+use sdl3::video::WindowContext;
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 use crate::actions::Action;
-use crate::errors::DataLoadError;
-use crate::gui::UiElement;
+use crate::errors::{DataLoadError, LayoutError};
+use crate::game_object::drawable::Sprite;
+use crate::gui::{TextFormat, UiElement};
 use crate::serialization::AssetId;
 use crate::serialization::font::FontDefinition;
-use crate::serialization::game::{AssetDefinition, GameData, TextureDefinition};
+use crate::serialization::game::{AssetDefinition, GameData, PrefabDefinition, TextureDefinition};
 use crate::serialization::level::LevelData;
+use crate::serialization::prefab::{apply_prefabs, Prefabs};
+use crate::texture_cache::TextureCache;
 
 pub struct GameAssets<'a> {
+	asset_file: String,
+	ttf_context: &'a Sdl3TtfContext,
 	pub game_data: GameData,
 	pub fonts: HashMap<AssetId, Font<'a>>,
 	pub surfaces: HashMap<AssetId, Surface<'a>>,
 	pub level_data: Vec<LevelData>,
 	pub gui_data: Vec<UiElement>,
 	pub keymap: HashMap<Keycode, Action>,
+	pub gamepad_map: HashMap<Button, Action>,
+	pub texture_cache: TextureCache<'a>,
+	/// index into the sprite sheets of the game data by id, every tile looks its sheet up
+	sprite_sheets: HashMap<AssetId, usize>,
 }
 
-impl<'a> GameAssets<'_> {
-	pub fn new (asset_file: &str, ttf_context: &'a Sdl3TtfContext) -> Self {
+impl<'a> GameAssets<'a> {
+	pub fn new (
+		asset_file: &str,
+		keymap: &HashMap<Keycode, Action>,
+		gamepad_map: &HashMap<Button, Action>,
+		ttf_context: &'a Sdl3TtfContext,
+		texture_creator: &'a TextureCreator<WindowContext>,
+	) -> Self {
 		let data_path = Path::new(asset_file);
 		let game_data = load_game_data(data_path).expect("Could not parse assets.json");
 
@@ -32,23 +51,83 @@ impl<'a> GameAssets<'_> {
 
 		let surfaces = load_surfaces(&game_data.textures);
 
-		let level_data = load_definitions(&game_data.levels);
+		let level_data = load_levels(&game_data);
 
 		let gui_data = load_definitions(&game_data.guis);
 
-		let keymap = load_keymap();
+		let keymap = load_keymap(keymap);
+
+		let gamepad_map = load_gamepad_map(gamepad_map);
+
+		let mut texture_cache = TextureCache::new(texture_creator);
+		texture_cache.load_textures(&surfaces);
+
+		let sprite_sheets = index_sprite_sheets(&game_data);
 
 		Self {
+			asset_file: asset_file.to_owned(),
+			ttf_context,
 			game_data,
 			fonts,
 			surfaces,
 			level_data,
 			gui_data,
 			keymap,
+			gamepad_map,
+			texture_cache,
+			sprite_sheets,
+		}
+	}
+
+	/// Reads all assets from disk again; cached textures are uploaded anew
+	pub fn reload(&mut self) {
+		let data_path = Path::new(&self.asset_file);
+		let game_data = load_game_data(data_path).expect("Could not parse assets.json");
+
+		self.fonts = load_fonts(&game_data.fonts, self.ttf_context);
+		self.surfaces = load_surfaces(&game_data.textures);
+		self.level_data = load_levels(&game_data);
+		self.gui_data = load_definitions(&game_data.guis);
+		self.sprite_sheets = index_sprite_sheets(&game_data);
+		self.game_data = game_data;
+
+		self.texture_cache.load_textures(&self.surfaces);
+	}
+
+	/// Replaces the bindings of every action in `keymap`, the other actions keep their defaults
+	pub fn update_keymap(&mut self, keymap: &HashMap<Keycode, Action>) {
+		self.keymap = load_keymap(keymap);
+	}
+
+	/// Resolves the frames of all guis against the window bounds
+	pub fn layout_gui(&mut self, window_bounds: FRect) -> Result<(), LayoutError> {
+		for gui in &mut self.gui_data {
+			gui.layout(window_bounds)?;
 		}
+
+		Ok(())
+	}
+
+	pub fn texture(&self, id: AssetId) -> Option<&Texture<'a>> {
+		self.texture_cache.texture(id)
+	}
+
+	/// The texture and the source rect of a frame of a sprite sheet
+	pub fn sprite_frame(&self, sprite: Sprite) -> Option<(AssetId, FRect)> {
+		let sheet = &self.game_data.sprite_sheets[*self.sprite_sheets.get(&sprite.sheet)?];
+
+		Some((sheet.texture_id, sheet.frame(sprite.frame)?))
+	}
+
+	pub fn text_texture(&mut self, text: &str, format: &TextFormat) -> &Texture<'a> {
+		self.texture_cache.text(text, format, &self.fonts)
 	}
 }
 
+fn index_sprite_sheets(game_data: &GameData) -> HashMap<AssetId, usize> {
+	game_data.sprite_sheets.iter().enumerate().map(|(i, sheet)| (sheet.id, i)).collect()
+}
+
 fn load_surfaces<'a>(texture_definitions: &[TextureDefinition]) -> HashMap<AssetId, Surface<'a>> {
 	let mut surfaces = HashMap::with_capacity(texture_definitions.len());
 
@@ -82,7 +161,7 @@ fn load_fonts<'a>(
 	fonts
 }
 
-fn load_definitions<T>(definitions: &[AssetDefinition]) -> Vec<T>
+pub fn load_definitions<T>(definitions: &[AssetDefinition]) -> Vec<T>
 where
 	T: DeserializeOwned,
 {
@@ -109,26 +188,62 @@ where
 	results
 }
 
-fn load_game_data(path: &Path) -> Result<GameData, DataLoadError> {
-	let Ok(file) = File::open(path) else {
-		return Err(DataLoadError { path: path.to_path_buf() });
-	};
+pub fn load_prefabs(definitions: &[PrefabDefinition]) -> Prefabs {
+	let prefabs: Vec<Value> = load_definitions(definitions);
+
+	definitions.iter().map(|d| d.id).zip(prefabs).collect()
+}
+
+/// Loads all levels and merges the prefabs their objects reference into them
+pub fn load_levels(game_data: &GameData) -> Vec<LevelData> {
+	let prefabs = load_prefabs(&game_data.prefabs);
+	let levels: Vec<Value> = load_definitions(&game_data.levels);
+
+	levels
+		.into_iter()
+		.zip(&game_data.levels)
+		.map(|(mut level, definition)| {
+			apply_prefabs(&mut level, &prefabs)
+				.unwrap_or_else(|err| panic!("Could not resolve prefabs in {}: {err}", definition.path));
+
+			let level: LevelData = serde_json::from_value(level)
+				.unwrap_or_else(|err| panic!("Could not parse asset json: {}: {err}", definition.path));
+
+			// behaviours and replays find objects by id
+			if let Some(id) = level.duplicate_id() {
+				panic!("Object id {id} is used more than once in {}", definition.path);
+			}
+
+			level
+		})
+		.collect()
+}
+
+pub fn load_game_data(path: &Path) -> Result<GameData, DataLoadError> {
+	let file = File::open(path).map_err(|err| DataLoadError {
+		path: path.to_path_buf(),
+		reason: err.to_string(),
+	})?;
 
 	let reader = BufReader::new(file);
 
-	serde_json::from_reader(reader).map_err(|_| DataLoadError { path: path.to_path_buf() })
+	serde_json::from_reader(reader).map_err(|err| DataLoadError {
+		path: path.to_path_buf(),
+		reason: err.to_string(),
+	})
 }
 
 
 
-fn load_keymap() -> HashMap<Keycode, Action> {
+fn load_keymap(overrides: &HashMap<Keycode, Action>) -> HashMap<Keycode, Action> {
 	let mut keymap = HashMap::new();
 
-	// Load keymap, will later be loaded from a config file
+	// Default keymap, every action found in the settings gets rebound completely
 
 	keymap.insert(Keycode::Escape, Action::Menu);
 	keymap.insert(Keycode::F2, Action::Debug);
 	keymap.insert(Keycode::F3, Action::FpsLimit);
+	keymap.insert(Keycode::F5, Action::Reload);
 	keymap.insert(Keycode::W, Action::MoveUp);
 	keymap.insert(Keycode::A, Action::MoveLeft);
 	keymap.insert(Keycode::S, Action::MoveDown);
@@ -138,5 +253,71 @@ fn load_keymap() -> HashMap<Keycode, Action> {
 	keymap.insert(Keycode::LCtrl, Action::Duck);
 	keymap.insert(Keycode::RCtrl, Action::Attack);
 
+	keymap.retain(|_, action| !overrides.values().any(|a| a == action));
+	keymap.extend(overrides);
+
 	keymap
 }
+
+fn load_gamepad_map(overrides: &HashMap<Button, Action>) -> HashMap<Button, Action> {
+	let mut gamepad_map = HashMap::new();
+
+	// Default mapping, every action found in the settings gets rebound completely
+
+	gamepad_map.insert(Button::Start, Action::Menu);
+	gamepad_map.insert(Button::Back, Action::Debug);
+	gamepad_map.insert(Button::DPadUp, Action::MoveUp);
+	gamepad_map.insert(Button::DPadLeft, Action::MoveLeft);
+	gamepad_map.insert(Button::DPadDown, Action::MoveDown);
+	gamepad_map.insert(Button::DPadRight, Action::MoveRight);
+	gamepad_map.insert(Button::South, Action::Jump);
+	gamepad_map.insert(Button::West, Action::Sprint);
+	gamepad_map.insert(Button::East, Action::Duck);
+	gamepad_map.insert(Button::North, Action::Attack);
+
+	gamepad_map.retain(|_, action| !overrides.values().any(|a| a == action));
+	gamepad_map.extend(overrides);
+
+	gamepad_map
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::path::Path;
+	use sdl3::keyboard::Keycode;
+	use crate::actions::Action;
+	use crate::game_assets::{load_game_data, load_keymap, load_levels};
+	use crate::game_object::behaviour::BehaviourType;
+
+	#[test]
+	fn test_keymap_overrides() {
+		let overrides = HashMap::from([
+			(Keycode::W, Action::Jump),
+			(Keycode::Up, Action::Jump),
+		]);
+
+		let keymap = load_keymap(&overrides);
+
+		assert_eq!(keymap.get(&Keycode::W), Some(&Action::Jump));
+		assert_eq!(keymap.get(&Keycode::Up), Some(&Action::Jump));
+		assert_eq!(keymap.get(&Keycode::Space), None);
+		assert_eq!(keymap.get(&Keycode::A), Some(&Action::MoveLeft));
+		assert!(!keymap.values().any(|a| *a == Action::MoveUp));
+	}
+
+	#[test]
+	fn test_levels_use_prefabs() {
+		let game_data = load_game_data(Path::new("./assets/assets.json")).unwrap();
+		let levels = load_levels(&game_data);
+		let boxes: Vec<_> = levels[0].objects.iter().filter(|o| o.id <= 3).collect();
+
+		assert_eq!(boxes.len(), 3);
+
+		for (i, object) in boxes.iter().enumerate() {
+			assert_eq!(object.bounds.w, 64.0);
+			assert_eq!(object.drawable.as_ref().unwrap().texture_id, Some(i as i32 + 1));
+			assert!(matches!(object.behaviours[..], [BehaviourType::Dvd(_), BehaviourType::Collision(_)]));
+		}
+	}
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult, WorldCommand};
+use crate::game_object::behaviour::controllable::MotionState;
+use crate::game_object::camera::CameraCommand;
+use crate::math::bounds::Bounds;
+use serde::{Deserialize, Serialize};
+
+fn _default_looping() -> bool {
+	true
+}
+
+/// What happens when an animation reaches a frame
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum AnimationEffect {
+	/// spawns a template of the level at the center of the object, e.g. dust from footsteps
+	Spawn {
+		template: String,
+	},
+	CameraShake {
+		strength: f32,
+		duration: f32,
+	},
+	/// eases the camera to the zoom, e.g. closer in during a special move
+	CameraZoom {
+		zoom: f32,
+	},
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AnimationEvent {
+	/// index into the frames of the clip
+	pub frame: usize,
+	pub effect: AnimationEffect,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AnimationClip {
+	/// frames of the sprite sheet in the order they are shown
+	pub frames: Vec<usize>,
+	/// seconds every frame is shown
+	pub frame_duration: f32,
+	/// clips that don't loop stop on their last frame
+	#[serde(default = "_default_looping")]
+	pub looping: bool,
+	#[serde(default)]
+	pub events: Vec<AnimationEvent>,
+}
+
+/// Plays clips of the sprite sheet of the drawable; a controllable behaviour before it picks the
+/// clip for what the object is doing
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AnimationBehaviour {
+	pub clips: HashMap<String, AnimationClip>,
+	/// the clip to play in each motion state; states without a clip keep the current one
+	#[serde(default)]
+	pub states: HashMap<MotionState, String>,
+	/// the clip that is playing
+	pub clip: String,
+	/// index into the frames of the clip
+	#[serde(default)]
+	pub frame: usize,
+	#[serde(default)]
+	pub timer: f32,
+	/// whether the events of the current frame have been fired
+	#[serde(skip)]
+	entered: bool,
+}
+
+impl AnimationBehaviour {
+	/// Starts a clip from its first frame; playing the current clip again does nothing
+	pub fn play(&mut self, clip: &str) {
+		if self.clip == clip || !self.clips.contains_key(clip) {
+			return;
+		}
+
+		self.clip = clip.to_owned();
+		self.frame = 0;
+		self.timer = 0.0;
+		self.entered = false;
+	}
+
+	pub fn tick(&mut self, params: BehaviourParameter, delta_t: f64) -> BehaviourResult {
+		if let Some(clip) = params.motion.and_then(|motion| self.states.get(&motion)).cloned() {
+			self.play(&clip);
+		}
+
+		let mut commands = Vec::new();
+
+		let Some(clip) = self.clips.get(&self.clip) else {
+			return BehaviourResult {
+				commands,
+				..Default::default()
+			};
+		};
+
+		if !self.entered {
+			self.entered = true;
+			commands.extend(events(clip, self.frame, &params));
+		}
+
+		self.timer += delta_t as f32;
+
+		while clip.frame_duration > 0.0 && self.timer >= clip.frame_duration {
+			let last = self.frame + 1 >= clip.frames.len();
+
+			if last && !clip.looping {
+				self.timer = 0.0;
+				break;
+			}
+
+			self.timer -= clip.frame_duration;
+			self.frame = if last { 0 } else { self.frame + 1 };
+
+			commands.extend(events(clip, self.frame, &params));
+		}
+
+		BehaviourResult {
+			commands,
+			frame: clip.frames.get(self.frame).copied(),
+			..Default::default()
+		}
+	}
+}
+
+/// The commands of the events on a frame of the clip
+fn events(clip: &AnimationClip, frame: usize, params: &BehaviourParameter) -> Vec<WorldCommand> {
+	clip.events
+		.iter()
+		.filter(|event| event.frame == frame)
+		.map(|event| match &event.effect {
+			AnimationEffect::Spawn { template } => WorldCommand::Spawn {
+				template: template.clone(),
+				position: params.bounds.center(),
+			},
+			AnimationEffect::CameraShake { strength, duration } => WorldCommand::Camera(CameraCommand::Shake {
+				strength: *strength,
+				duration: *duration,
+			}),
+			AnimationEffect::CameraZoom { zoom } => WorldCommand::Camera(CameraCommand::Zoom(*zoom)),
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::actions::Action;
+	use crate::game_object::behaviour::animation::AnimationBehaviour;
+	use crate::game_object::behaviour::controllable::MotionState;
+	use crate::game_object::behaviour::{BehaviourParameter, WorldCommand};
+	use crate::game_object::broadphase::Broadphase;
+	use crate::game_object::camera::CameraCommand;
+	use crate::input::InputState;
+	use crate::math::bounds::Bounds;
+	use crate::test_level::{input, object_bounds, player_bounds, script, TestLevel};
+
+	fn animation() -> AnimationBehaviour {
+		serde_json::from_str(r#"{
+			"clips": {
+				"idle": { "frames": [0, 1], "frame_duration": 0.5 },
+				"run": {
+					"frames": [2, 3, 4],
+					"frame_duration": 0.1,
+					"events": [{ "frame": 1, "effect": { "type": "Spawn", "template": "dust" } }]
+				},
+				"jump": {
+					"frames": [5, 6],
+					"frame_duration": 0.1,
+					"looping": false,
+					"events": [{ "frame": 0, "effect": { "type": "CameraZoom", "zoom": 1.5 } }]
+				}
+			},
+			"states": { "Idle": "idle", "Run": "run", "Jump": "jump" },
+			"clip": "idle"
+		}"#).unwrap()
+	}
+
+	fn tick(animation: &mut AnimationBehaviour, motion: MotionState, delta_t: f64) -> (Option<usize>, Vec<WorldCommand>) {
+		let input = InputState::default();
+		let broadphase = Broadphase::default();
+		let result = animation.tick(BehaviourParameter {
+			id: 1,
+			bounds: FRect { x: 0.0, y: 0.0, w: 10.0, h: 10.0 },
+			input: &input,
+			world_bounds: FRect { x: 0.0, y: 0.0, w: 100.0, h: 100.0 },
+			broadphase: &broadphase,
+			collider: None,
+			collisions: &Vec::new(),
+			force: None,
+			impulse: None,
+			motion: Some(motion),
+			speed_control: None,
+		}, delta_t);
+
+		(result.frame, result.commands)
+	}
+
+	#[test]
+	fn test_loop_and_events() {
+		let mut animation = animation();
+
+		assert_eq!(tick(&mut animation, MotionState::Idle, 0.25).0, Some(0));
+		assert_eq!(tick(&mut animation, MotionState::Idle, 0.25).0, Some(1));
+		assert_eq!(tick(&mut animation, MotionState::Idle, 0.5).0, Some(0));
+
+		// switching the clip starts it from the first frame
+		let (frame, commands) = tick(&mut animation, MotionState::Run, 0.05);
+		assert_eq!(frame, Some(2));
+		assert!(commands.is_empty());
+
+		let (frame, commands) = tick(&mut animation, MotionState::Run, 0.05);
+		assert_eq!(frame, Some(3));
+		assert!(matches!(&commands[..], [WorldCommand::Spawn { template, .. }] if template == "dust"));
+
+		// states without a clip keep playing the current one
+		assert_eq!(tick(&mut animation, MotionState::Walk, 0.2).0, Some(2));
+	}
+
+	#[test]
+	fn test_clip_without_loop_stops() {
+		let mut animation = animation();
+
+		let (frame, commands) = tick(&mut animation, MotionState::Jump, 0.1);
+		assert_eq!(frame, Some(6));
+		assert_eq!(commands, vec![WorldCommand::Camera(CameraCommand::Zoom(1.5))]);
+
+		assert_eq!(tick(&mut animation, MotionState::Jump, 1.0).0, Some(6));
+	}
+
+	#[test]
+	fn test_animated_player_moves() {
+		let mut runner = TestLevel::new()
+			.start_y(516.0)
+			.animation(r#"{
+				"clips": { "idle": { "frames": [0], "frame_duration": 0.1 } },
+				"clip": "idle"
+			}"#)
+			// an object whose animation sits between its controllable and physics behaviours
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 100, "y": 100, "w": 32, "h": 32 },
+				"behaviours": [
+					{ "type": "Controllable", "speed": 5, "run_speed": 15 },
+					{ "type": "Animation", "clips": { "idle": { "frames": [0], "frame_duration": 0.1 } }, "clip": "idle" },
+					{ "type": "Physics", "speed": { "x": 0, "y": 0 }, "mass": 1 }
+				]
+			}"#)
+			.runner();
+
+		runner.run(60, &script([(60, input([Action::MoveRight, Action::Sprint, Action::Jump]))]));
+
+		assert!(player_bounds(&runner).left() > 150.0);
+		assert!(player_bounds(&runner).bottom() < 570.0);
+		assert!(object_bounds(&runner, 2).left() > 150.0);
+	}
+}
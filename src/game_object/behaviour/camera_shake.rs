@@ -0,0 +1,91 @@
+use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult, WorldCommand};
+use crate::game_object::camera::CameraCommand;
+use serde::{Deserialize, Serialize};
+
+/// Shakes the camera when the object hits something; needs a collision behaviour before it
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CameraShakeBehaviour {
+	/// how far the view moves at most, in pixels
+	pub strength: f32,
+	/// seconds until the shake has faded out
+	pub duration: f32,
+	/// ids of the objects it touched in the last tick
+	#[serde(skip)]
+	touching: Vec<i32>,
+}
+
+impl CameraShakeBehaviour {
+	pub fn tick(&mut self, params: BehaviourParameter, _: f64) -> BehaviourResult {
+		let touching: Vec<i32> = params.collisions.iter().filter(|c| c.is_solid()).map(|c| c.id).collect();
+
+		// only a new contact is an impact, resting on something is not
+		let commands = if touching.iter().any(|id| !self.touching.contains(id)) {
+			vec![WorldCommand::Camera(CameraCommand::Shake {
+				strength: self.strength,
+				duration: self.duration,
+			})]
+		} else {
+			Vec::new()
+		};
+
+		self.touching = touching;
+
+		BehaviourResult {
+			commands,
+			..Default::default()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::game_object::behaviour::camera_shake::CameraShakeBehaviour;
+	use crate::game_object::behaviour::BehaviourParameter;
+	use crate::game_object::broadphase::Broadphase;
+	use crate::game_object::{BodyType, CollisionInfo, CollisionType, PhysicsVector};
+	use crate::input::InputState;
+
+	fn shakes(shake: &mut CameraShakeBehaviour, touching: &[i32]) -> bool {
+		let input = InputState::default();
+		let broadphase = Broadphase::default();
+		let collisions: Vec<CollisionInfo> = touching
+			.iter()
+			.map(|id| CollisionInfo {
+				id: *id,
+				bounds: FRect { x: 0.0, y: 10.0, w: 10.0, h: 0.0 },
+				body: BodyType::Static,
+				collision: CollisionType::Solid,
+				normal: PhysicsVector { x: 0.0, y: -1.0 },
+			})
+			.collect();
+
+		let result = shake.tick(BehaviourParameter {
+			id: 1,
+			bounds: FRect { x: 0.0, y: 0.0, w: 10.0, h: 10.0 },
+			input: &input,
+			world_bounds: FRect { x: 0.0, y: 0.0, w: 100.0, h: 100.0 },
+			broadphase: &broadphase,
+			collider: None,
+			collisions: &collisions,
+			force: None,
+			impulse: None,
+			motion: None,
+			speed_control: None,
+		}, 1.0 / 60.0);
+
+		!result.commands.is_empty()
+	}
+
+	#[test]
+	fn test_shakes_on_new_contacts() {
+		let mut shake: CameraShakeBehaviour = serde_json::from_str(r#"{ "strength": 8, "duration": 0.3 }"#).unwrap();
+
+		assert!(shakes(&mut shake, &[2]));
+		// resting on the ground
+		assert!(!shakes(&mut shake, &[2]));
+		// sliding into a wall while on the ground
+		assert!(shakes(&mut shake, &[2, 3]));
+		assert!(!shakes(&mut shake, &[3]));
+	}
+}
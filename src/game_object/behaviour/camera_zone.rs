@@ -0,0 +1,41 @@
+use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult, WorldCommand};
+use crate::game_object::camera::CameraCommand;
+use serde::{Deserialize, Serialize};
+
+fn _default_target() -> i32 {
+	-1
+}
+
+/// Fits the camera to the object while the target is inside it and follows the target again once
+/// it has left
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct CameraZoneBehaviour {
+	/// id of the object that triggers the zone; the player if not set
+	#[serde(default = "_default_target")]
+	pub target: i32,
+	#[serde(skip)]
+	inside: bool,
+}
+
+impl CameraZoneBehaviour {
+	pub fn tick(&mut self, params: BehaviourParameter, _: f64) -> BehaviourResult {
+		let inside = params
+			.broadphase
+			.query_rect(params.bounds)
+			.into_iter()
+			.any(|other| other.id == self.target);
+
+		let commands = match (self.inside, inside) {
+			(false, true) => vec![WorldCommand::Camera(CameraCommand::Fit(params.bounds))],
+			(true, false) => vec![WorldCommand::Camera(CameraCommand::Follow)],
+			_ => Vec::new(),
+		};
+
+		self.inside = inside;
+
+		BehaviourResult {
+			commands,
+			..Default::default()
+		}
+	}
+}
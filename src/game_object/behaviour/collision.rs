@@ -1,49 +1,309 @@
 use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult};
 use crate::math::bounds::Bounds;
+use sdl3::render::FRect;
 use serde::{Deserialize, Serialize};
-use crate::game_object::{BoundInfo, ObjectMask};
+use crate::actions::Action;
+use crate::game_object::{CollisionInfo, CollisionType, ObjectMask, PhysicsVector};
+use crate::input::InputState;
+
+fn _default_drop_on_duck() -> bool {
+	true
+}
+
+/// How far a body can be below the top of a one-way platform and still land on it
+static ONE_WAY_TOLERANCE: f32 = 4.0;
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 pub struct CollisionBehaviour {
 	#[serde(default)]
 	pub mask: ObjectMask,
+	/// whether holding `MoveDown` or `Duck` drops the object through one-way platforms
+	#[serde(default)]
+	pub drop_through: bool,
+	/// whether `Duck` drops through as well; objects that crouch on `Duck` only drop with `MoveDown`
+	#[serde(default = "_default_drop_on_duck")]
+	pub drop_on_duck: bool,
+}
+
+impl Default for CollisionBehaviour {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl CollisionBehaviour {
     pub fn new() -> Self {
-        Self { 
-			mask: ObjectMask::default()
+        Self {
+			mask: ObjectMask::default(),
+			drop_through: false,
+			drop_on_duck: _default_drop_on_duck(),
 		}
     }
+
+	/// Objects and colliders without a mask collide with everything
+	pub fn collides_with(&self, mask: ObjectMask) -> bool {
+		mask == 0 || self.mask == 0 || mask & self.mask != 0
+	}
+
+	pub fn is_dropping(&self, input: &InputState) -> bool {
+		self.drop_through && (input.pressed(Action::MoveDown) || (self.drop_on_duck && input.pressed(Action::Duck)))
+	}
+
+	/// The contact with an object of the given collision type; one-way platforms are ignored
+	/// while dropping through them
+	pub fn contact(&self, collision: CollisionType, bounds: FRect, other: FRect, input: &InputState) -> Option<(PhysicsVector, f32)> {
+		match collision {
+			CollisionType::Solid | CollisionType::Trigger | CollisionType::Climbable => contact(bounds, other),
+			CollisionType::OneWay if self.is_dropping(input) => None,
+			CollisionType::OneWay => one_way_contact(bounds, other),
+		}
+	}
 }
 
 impl CollisionBehaviour {
     pub fn tick(&mut self, params: BehaviourParameter, _: f64) -> BehaviourResult {
-        let mut collisions: Vec<BoundInfo> = Vec::new();
+        let mut collisions: Vec<CollisionInfo> = Vec::new();
 
         let bounds = params.bounds;
 
-        for i in 0..params.other_bounds.len() {
-            let (id, rect, mask) = params.other_bounds[i];
-
-            if id == params.id {
+        for other in params.broadphase.query_rect(bounds) {
+            if other.id == params.id {
                 continue;
             }
 
-			if mask != 0 && self.mask != 0 && mask & self.mask == 0 {
+			if !self.collides_with(other.mask) {
 				continue;
 			}
-			
-            if rect.intersects(bounds) {
-                collisions.push((id, rect.intersection(bounds), mask))
+
+            if let Some((normal, _)) = self.contact(other.collision, bounds, other.bounds, params.input) {
+                collisions.push(CollisionInfo {
+					id: other.id,
+					bounds: other.bounds.intersection(bounds),
+					body: other.body,
+					collision: other.collision,
+					normal,
+				})
             }
         }
 
         BehaviourResult {
-            bounds: None,
             collisions: Some(collisions),
-            force: None,
-            impulse: None,
+            ..Default::default()
         }
     }
 }
+
+/// The normal pointing from `other` towards `bounds` and the overlap along it; the axis with the
+/// smaller overlap is the shortest way to separate both rects
+pub fn contact(bounds: FRect, other: FRect) -> Option<(PhysicsVector, f32)> {
+	if !bounds.intersects(other) {
+		return None;
+	}
+
+	let overlap = bounds.intersection(other);
+	let center = bounds.center();
+	let other_center = other.center();
+
+	if overlap.w < overlap.h {
+		let x = if center.x < other_center.x { -1.0 } else { 1.0 };
+
+		Some((PhysicsVector { x, y: 0.0 }, overlap.w))
+	} else {
+		let y = if center.y < other_center.y { -1.0 } else { 1.0 };
+
+		Some((PhysicsVector { x: 0.0, y }, overlap.h))
+	}
+}
+
+/// The contact with a one-way platform: only bodies that stand on it or have sunk in by at most
+/// `ONE_WAY_TOLERANCE` are pushed upwards, everything else passes through
+pub fn one_way_contact(bounds: FRect, other: FRect) -> Option<(PhysicsVector, f32)> {
+	let overlaps_horizontally = bounds.right() > other.left() && bounds.left() < other.right();
+	let penetration = bounds.bottom() - other.top();
+
+	if !overlaps_horizontally || !(0.0..=ONE_WAY_TOLERANCE).contains(&penetration) {
+		return None;
+	}
+
+	Some((PhysicsVector { x: 0.0, y: -1.0 }, penetration))
+}
+
+/// When and where `bounds` moving by `motion` first touches `other`: the fraction of the motion
+/// in `0.0..1.0` and the normal pointing away from `other`. Rects that already overlap or only
+/// graze a corner are not hit.
+pub fn sweep(bounds: FRect, motion: PhysicsVector, other: FRect) -> Option<(f32, PhysicsVector)> {
+	let (entry_x, exit_x) = axis_times(bounds.left(), bounds.right(), other.left(), other.right(), motion.x)?;
+	let (entry_y, exit_y) = axis_times(bounds.top(), bounds.bottom(), other.top(), other.bottom(), motion.y)?;
+
+	let entry = f32::max(entry_x, entry_y);
+	let exit = f32::min(exit_x, exit_y);
+
+	if entry >= exit || !(0.0..1.0).contains(&entry) {
+		return None;
+	}
+
+	let normal = if entry_x > entry_y {
+		PhysicsVector { x: -motion.x.signum(), y: 0.0 }
+	} else {
+		PhysicsVector { x: 0.0, y: -motion.y.signum() }
+	};
+
+	Some((entry, normal))
+}
+
+/// Fractions of `motion` at which the interval `a` starts and stops overlapping `b`
+fn axis_times(a_min: f32, a_max: f32, b_min: f32, b_max: f32, motion: f32) -> Option<(f32, f32)> {
+	if motion > 0.0 {
+		Some(((b_min - a_max) / motion, (b_max - a_min) / motion))
+	} else if motion < 0.0 {
+		Some(((b_max - a_min) / motion, (b_min - a_max) / motion))
+	} else if a_max > b_min && a_min < b_max {
+		Some((f32::NEG_INFINITY, f32::INFINITY))
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::actions::Action;
+	use crate::game_object::behaviour::collision::{contact, one_way_contact, sweep};
+	use crate::game_object::PhysicsVector;
+	use crate::input::InputState;
+	use crate::math::bounds::Bounds;
+	use crate::test_level::{input, player_bounds, script, TestLevel};
+
+	#[test]
+	fn test_contact() {
+		let ground = FRect::new(0.0, 100.0, 200.0, 20.0);
+
+		let (normal, penetration) = contact(FRect::new(50.0, 60.0, 40.0, 44.0), ground).unwrap();
+		assert_eq!(normal, PhysicsVector { x: 0.0, y: -1.0 });
+		assert_eq!(penetration, 4.0);
+
+		let (normal, penetration) = contact(FRect::new(195.0, 90.0, 40.0, 40.0), ground).unwrap();
+		assert_eq!(normal, PhysicsVector { x: 1.0, y: 0.0 });
+		assert_eq!(penetration, 5.0);
+
+		let (normal, penetration) = contact(FRect::new(50.0, 60.0, 40.0, 40.0), ground).unwrap();
+		assert_eq!(normal, PhysicsVector { x: 0.0, y: -1.0 });
+		assert_eq!(penetration, 0.0);
+
+		assert!(contact(FRect::new(50.0, 0.0, 40.0, 40.0), ground).is_none());
+	}
+
+	#[test]
+	fn test_one_way_contact() {
+		let platform = FRect::new(0.0, 100.0, 200.0, 4.0);
+
+		let (normal, penetration) = one_way_contact(FRect::new(50.0, 38.0, 40.0, 64.0), platform).unwrap();
+		assert_eq!(normal, PhysicsVector { x: 0.0, y: -1.0 });
+		assert_eq!(penetration, 2.0);
+
+		assert_eq!(one_way_contact(FRect::new(50.0, 36.0, 40.0, 64.0), platform).unwrap().1, 0.0);
+
+		// halfway through from below
+		assert!(one_way_contact(FRect::new(50.0, 70.0, 40.0, 64.0), platform).is_none());
+		assert!(one_way_contact(FRect::new(200.0, 36.0, 40.0, 64.0), platform).is_none());
+	}
+
+	#[test]
+	fn test_sweep() {
+		let platform = FRect::new(0.0, 100.0, 200.0, 2.0);
+		let falling = FRect::new(50.0, 0.0, 40.0, 40.0);
+
+		// would end up far below the platform
+		let (time, normal) = sweep(falling, PhysicsVector { x: 0.0, y: 300.0 }, platform).unwrap();
+		assert_eq!(time, 0.2);
+		assert_eq!(normal, PhysicsVector { x: 0.0, y: -1.0 });
+
+		// too short to reach it
+		assert!(sweep(falling, PhysicsVector { x: 0.0, y: 50.0 }, platform).is_none());
+
+		// resting on it and sliding along
+		let resting = FRect::new(50.0, 60.0, 40.0, 40.0);
+		assert!(sweep(resting, PhysicsVector { x: 30.0, y: 0.0 }, platform).is_none());
+		assert_eq!(sweep(resting, PhysicsVector { x: 30.0, y: 5.0 }, platform).unwrap().0, 0.0);
+
+		let (time, normal) = sweep(FRect::new(-100.0, 90.0, 40.0, 20.0), PhysicsVector { x: 120.0, y: 0.0 }, platform).unwrap();
+		assert_eq!(time, 0.5);
+		assert_eq!(normal, PhysicsVector { x: -1.0, y: 0.0 });
+	}
+
+	#[test]
+	fn test_one_way_platform() {
+		// the player starts on the ground, right below the platform
+		let mut runner = TestLevel::new()
+			.start_y(516.0)
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 0, "y": 500, "w": 800, "h": 4 },
+				"collision": "OneWay",
+				"drawable": { "z": { "Foreground": 1 } },
+				"behaviours": [{ "type": "Collision" }]
+			}"#)
+			.runner();
+
+		// jumps up through the platform and lands on top of it
+		runner.run(120, &script([(60, input([Action::Jump]))]));
+		assert!((player_bounds(&runner).bottom() - 500.0).abs() < 0.01);
+
+		let script = script([
+			(120, InputState::default()),
+			(30, input([Action::Duck])),
+			(90, input([Action::MoveDown])),
+		]);
+
+		// ducking crouches on the platform instead of dropping through it
+		runner.run(30, &script);
+		assert_eq!(player_bounds(&runner).h, 32.0);
+		assert!((player_bounds(&runner).bottom() - 500.0).abs() < 0.01);
+
+		// holding down drops through it again
+		runner.run(90, &script);
+		assert!((player_bounds(&runner).bottom() - 580.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn test_walk_through_trigger() {
+		let mut runner = TestLevel::new()
+			.start_y(516.0)
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 300, "y": 0, "w": 20, "h": 580 },
+				"collision": "Trigger",
+				"drawable": { "z": { "Foreground": 1 } },
+				"behaviours": [{ "type": "Collision" }]
+			}"#)
+			.runner();
+
+		runner.run(120, &script([(120, input([Action::MoveRight, Action::Sprint]))]));
+
+		assert!(player_bounds(&runner).left() > 320.0);
+		assert!((player_bounds(&runner).bottom() - 580.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn test_duck_drops_without_crouching() {
+		let mut runner = TestLevel::new()
+			.start_y(436.0)
+			.controllable(r#"{ "speed": 5, "run_speed": 15, "can_duck": false }"#)
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 0, "y": 500, "w": 800, "h": 4 },
+				"collision": "OneWay",
+				"behaviours": [{ "type": "Collision" }]
+			}"#)
+			.runner();
+
+		let script = script([(30, InputState::default()), (60, input([Action::Duck]))]);
+
+		runner.run(30, &script);
+		assert!((player_bounds(&runner).bottom() - 500.0).abs() < 0.01);
+
+		runner.run(60, &script);
+		assert_eq!(player_bounds(&runner).h, 64.0);
+		assert!((player_bounds(&runner).bottom() - 580.0).abs() < 0.01);
+	}
+}
@@ -1,17 +1,163 @@
-use crate::game_object::{PhysicsVector};
+use sdl3::render::FRect;
+use crate::game_object::{BodyType, CollisionInfo, CollisionType, PhysicsVector};
 use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult};
 use crate::math::bounds::Bounds;
-use crate::math::VectorOps;
+use crate::serialization::AssetId;
 use serde::{Deserialize, Serialize};
 use crate::actions::Action;
+use crate::input::InputAxis;
+
+fn _default_jump_impulse() -> f32 {
+	15.0
+}
+
+fn _default_coyote_time() -> f32 {
+	0.1
+}
+
+fn _default_jump_buffer() -> f32 {
+	0.1
+}
+
+fn _default_jump_cut() -> f32 {
+	0.5
+}
+
+fn _default_max_fall_speed() -> f32 {
+	20.0
+}
+
+fn _default_air_control() -> f32 {
+	1.0
+}
+
+fn _default_true() -> bool {
+	true
+}
+
+fn _default_climb_speed() -> f32 {
+	3.0
+}
+
+fn _default_wall_slide_speed() -> f32 {
+	2.0
+}
+
+fn _default_wall_jump_impulse() -> PhysicsVector {
+	PhysicsVector { x: 10.0, y: 15.0 }
+}
+
+fn _default_duck_height() -> f32 {
+	0.5
+}
+
+fn _default_duck_speed() -> f32 {
+	0.5
+}
+
+/// What a controllable object is doing, e.g. to pick an animation
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MotionState {
+	#[default]
+	Idle,
+	Walk,
+	Run,
+	Jump,
+	Fall,
+	Climb,
+	WallSlide,
+	Duck,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroundState {
+	#[default]
+	Grounded,
+	/// rising after a jump, releasing the jump cuts it short
+	Jumping,
+	/// falling, or walked off a ledge
+	Airborne,
+	/// holding on to a ladder, gravity doesn't pull
+	Climbing,
+}
+
+/// Changes to the speed of the physics behaviour after a controllable behaviour
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedControl {
+	/// meters per second
+	pub max_fall_speed: f32,
+	/// multiplies the upward speed in this tick
+	pub rise_factor: f32,
+	/// replaces the vertical speed and gravity, e.g. on a ladder; meters per second
+	pub vertical_speed: Option<f32>,
+}
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 pub struct ControllableBehaviour {
 	pub speed: f32,
 	pub run_speed: f32,
-	pub jumping: f32,
-	pub velocity: PhysicsVector,
-	pub acceleration: PhysicsVector,
+	#[serde(default = "_default_jump_impulse")]
+	pub jump_impulse: f32,
+	/// seconds after walking off a ledge in which a jump still works
+	#[serde(default = "_default_coyote_time")]
+	pub coyote_time: f32,
+	/// seconds a jump pressed in the air is remembered, so that it happens once the object lands
+	#[serde(default = "_default_jump_buffer")]
+	pub jump_buffer: f32,
+	/// multiplies the upward speed when the jump is released while rising
+	#[serde(default = "_default_jump_cut")]
+	pub jump_cut: f32,
+	/// meters per second
+	#[serde(default = "_default_max_fall_speed")]
+	pub max_fall_speed: f32,
+	/// how much of the movement force applies in the air
+	#[serde(default = "_default_air_control")]
+	pub air_control: f32,
+	/// whether moving up or down on a climbable object holds on to it
+	#[serde(default = "_default_true")]
+	pub can_climb: bool,
+	/// meters per second
+	#[serde(default = "_default_climb_speed")]
+	pub climb_speed: f32,
+	/// whether pressing into a wall while falling slows the fall
+	#[serde(default = "_default_true")]
+	pub can_wall_slide: bool,
+	/// max fall speed while sliding down a wall, in meters per second
+	#[serde(default = "_default_wall_slide_speed")]
+	pub wall_slide_speed: f32,
+	/// whether jumping in the air next to a wall jumps away from it
+	#[serde(default = "_default_true")]
+	pub can_wall_jump: bool,
+	/// away from the wall and upwards
+	#[serde(default = "_default_wall_jump_impulse")]
+	pub wall_jump_impulse: PhysicsVector,
+	/// whether holding duck on the ground crouches
+	#[serde(default = "_default_true")]
+	pub can_duck: bool,
+	/// part of the standing height that is left while ducking
+	#[serde(default = "_default_duck_height")]
+	pub duck_height: f32,
+	/// multiplies the movement force while ducking
+	#[serde(default = "_default_duck_speed")]
+	pub duck_speed: f32,
+	/// shown instead of the texture of the drawable while ducking
+	#[serde(default)]
+	pub duck_texture: Option<AssetId>,
+	/// the height before ducking, set while the object is ducking
+	#[serde(skip)]
+	pub standing_height: Option<f32>,
+	#[serde(skip)]
+	pub state: GroundState,
+	#[serde(skip)]
+	pub coyote_timer: f32,
+	#[serde(skip)]
+	pub buffer_timer: f32,
+	/// whether the jump was pressed in the last tick; only pressing it again jumps
+	#[serde(skip)]
+	pub jump_held: bool,
+	/// bottom of the object in the last tick, to tell jumping from falling
+	#[serde(skip)]
+	previous_bottom: Option<f32>,
 }
 
 impl ControllableBehaviour {
@@ -19,9 +165,28 @@ impl ControllableBehaviour {
         Self {
             speed,
             run_speed,
-			jumping: 0.0,
-			velocity: PhysicsVector::default(),
-			acceleration: PhysicsVector::default(),
+			jump_impulse: _default_jump_impulse(),
+			coyote_time: _default_coyote_time(),
+			jump_buffer: _default_jump_buffer(),
+			jump_cut: _default_jump_cut(),
+			max_fall_speed: _default_max_fall_speed(),
+			air_control: _default_air_control(),
+			can_climb: _default_true(),
+			climb_speed: _default_climb_speed(),
+			can_wall_slide: _default_true(),
+			wall_slide_speed: _default_wall_slide_speed(),
+			can_wall_jump: _default_true(),
+			wall_jump_impulse: _default_wall_jump_impulse(),
+			can_duck: _default_true(),
+			duck_height: _default_duck_height(),
+			duck_speed: _default_duck_speed(),
+			duck_texture: None,
+			standing_height: None,
+			state: GroundState::default(),
+			coyote_timer: 0.0,
+			buffer_timer: 0.0,
+			jump_held: false,
+			previous_bottom: None,
         }
     }
 }
@@ -29,57 +194,561 @@ impl ControllableBehaviour {
 impl ControllableBehaviour {
 	pub fn tick(&mut self, params: BehaviourParameter, delta_t: f64) -> BehaviourResult {
         let sec = delta_t as f32;
-		let bounds = params.bounds;
-        let actions = params.actions;
+        let input = params.input;
 		let mut force = PhysicsVector::default();
 		let mut impulse = PhysicsVector::default();
+		let mut rise_factor = 1.0;
+		let mut max_fall_speed = self.max_fall_speed;
 
-        let speed = if actions.contains(&Action::Sprint) {
+        let mut speed = if input.pressed(Action::Sprint) {
             self.run_speed
         } else {
             self.speed
         };
 
-		let mut in_air = true;
+		// standing on something pushes upwards
+		let grounded = params.collisions.iter().any(|c| c.is_solid() && c.normal.y < 0.0);
+
+		// the bottom, since ducking moves the top
+		let rising = self.previous_bottom.is_some_and(|bottom| params.bounds.bottom() < bottom);
+
+		let on_ladder = self.can_climb && params.collisions.iter().any(|c| is_ladder(c, params.bounds.center().x));
+
+		// pushed away from a wall next to the object; bodies that can be pushed aren't walls
+		let wall = params.collisions
+			.iter()
+			.find(|c| c.is_solid() && c.normal.x != 0.0 && c.body != BodyType::Dynamic)
+			.map(|c| c.normal.x);
 
-		for i in 0..params.collisions.len() {
-			let (_, collision, _) = params.collisions[i];
+		// down is positive
+		let move_y = input.axis(InputAxis::MoveY);
+
+		self.previous_bottom = Some(params.bounds.bottom());
+
+		let jump_pressed = input.pressed(Action::Jump);
+
+		if jump_pressed && !self.jump_held {
+			self.buffer_timer = self.jump_buffer;
+		}
 
-			if collision.w > collision.h && collision.top() > bounds.center().y {
-				in_air = false;
+		self.jump_held = jump_pressed;
 
-				break;
+		self.state = match self.state {
+			GroundState::Climbing if on_ladder && !grounded => GroundState::Climbing,
+			// rising after a jump off a ladder doesn't grab it again
+			GroundState::Grounded | GroundState::Airborne | GroundState::Climbing if on_ladder && move_y != 0.0 => GroundState::Climbing,
+			_ if grounded => GroundState::Grounded,
+			GroundState::Jumping if !rising => GroundState::Airborne,
+			GroundState::Jumping if !jump_pressed => {
+				rise_factor = self.jump_cut;
+
+				GroundState::Airborne
 			}
+			GroundState::Jumping => GroundState::Jumping,
+			GroundState::Grounded | GroundState::Airborne | GroundState::Climbing => GroundState::Airborne,
+		};
+
+		let climbing = self.state == GroundState::Climbing;
+
+		if grounded {
+			self.coyote_timer = self.coyote_time;
+		}
+
+		if self.buffer_timer > 0.0 && (grounded || climbing || self.coyote_timer > 0.0) {
+			impulse += PhysicsVector { x: 0.0, y: -self.jump_impulse };
+
+			self.state = GroundState::Jumping;
+			self.buffer_timer = 0.0;
+			self.coyote_timer = 0.0;
+		} else if let Some(away) = wall.filter(|_| self.buffer_timer > 0.0 && self.can_wall_jump) {
+			impulse += PhysicsVector { x: away * self.wall_jump_impulse.x, y: -self.wall_jump_impulse.y };
+
+			self.state = GroundState::Jumping;
+			self.buffer_timer = 0.0;
 		}
 
-		if !in_air && actions.contains(&Action::Jump) && self.jumping == 0.0 {
-			self.jumping = 0.3;	// 0.3 sec cooldown for jumping; this is just a dirty fix for multi-jumps
-			impulse += PhysicsVector { x: 0.0, y: -self.run_speed };
+		self.coyote_timer = f32::max(0.0, self.coyote_timer - sec);
+		self.buffer_timer = f32::max(0.0, self.buffer_timer - sec);
+
+		let bounds = self.duck(&params, grounded && !climbing);
+
+		if self.standing_height.is_some() {
+			speed *= self.duck_speed;
 		}
 
-		self.jumping = f32::max(0.0, self.jumping - sec);
+        // analog input moves slower than the full speed
+		let move_x = input.axis(InputAxis::MoveX);
+		let control = if grounded { 1.0 } else { self.air_control };
 
-        // if actions.contains(Action::MoveUp) {
-        //     force -= PhysicsVector { x: 0.0, y: 1.0 };
-        // } else if actions.contains(Action::MoveDown) {
-        //     force += PhysicsVector { x: 0.0, y: 1.0 };
-        // }
+		force += PhysicsVector { x: move_x, y: 0.0 } * (speed * control);
 
-        if actions.contains(&Action::MoveLeft) {
-			force -= PhysicsVector { x: 1.0, y: 0.0 };
-        } else if actions.contains(&Action::MoveRight) {
-			force += PhysicsVector { x: 1.0, y: 0.0 };
-        }
+		// falling while pressing into a wall
+		let wall_sliding = self.can_wall_slide
+			&& self.state == GroundState::Airborne
+			&& !rising
+			&& wall.is_some_and(|away| move_x * away < 0.0);
 
-		if force.len() != 0.0 {
-			force = force.normal() * speed;
+		if wall_sliding {
+			max_fall_speed = f32::min(max_fall_speed, self.wall_slide_speed);
 		}
 
+		let vertical_speed = (self.state == GroundState::Climbing).then_some(move_y * self.climb_speed);
+
+		let motion = if self.state == GroundState::Climbing {
+			MotionState::Climb
+		} else if wall_sliding {
+			MotionState::WallSlide
+		} else if self.state == GroundState::Jumping || (!grounded && rising) {
+			MotionState::Jump
+		} else if !grounded {
+			MotionState::Fall
+		} else if self.standing_height.is_some() {
+			MotionState::Duck
+		} else if move_x == 0.0 {
+			MotionState::Idle
+		} else if input.pressed(Action::Sprint) {
+			MotionState::Run
+		} else {
+			MotionState::Walk
+		};
+
         BehaviourResult {
-            bounds: None,
-            collisions: None,
+            bounds,
 			force: Some(force),
 			impulse: Some(impulse),
+			motion: Some(motion),
+			texture: self.standing_height.and(self.duck_texture),
+			speed_control: Some(SpeedControl {
+				max_fall_speed,
+				rise_factor,
+				vertical_speed,
+			}),
+			..Default::default()
         }
     }
 }
+
+impl ControllableBehaviour {
+	/// Crouches while duck is held on the ground and stands up again once it is released and there is
+	/// room above; the bounds keep their bottom. Returns the resized bounds if they changed.
+	fn duck(&mut self, params: &BehaviourParameter, can_start: bool) -> Option<FRect> {
+		let bounds = params.bounds;
+		let duck_pressed = self.can_duck && params.input.pressed(Action::Duck);
+
+		match self.standing_height {
+			None if duck_pressed && can_start => {
+				self.standing_height = Some(bounds.h);
+
+				Some(FRect { y: bounds.bottom() - bounds.h * self.duck_height, h: bounds.h * self.duck_height, ..bounds })
+			}
+			Some(height) if !duck_pressed => {
+				let standing = FRect { y: bounds.bottom() - height, h: height, ..bounds };
+
+				// only the part above the ducking object, the ground and walls only touch it
+				let headroom = FRect { h: height - bounds.h, ..standing };
+
+				// dynamic bodies get pushed away instead
+				let blocked = params.broadphase.query_rect(headroom).iter().any(|other| {
+					let overlap = other.bounds.intersection(headroom);
+
+					other.id != params.id
+						&& other.collider
+						&& other.body != BodyType::Dynamic
+						&& other.collision == CollisionType::Solid
+						&& params.collider.is_some_and(|c| c.collides_with(other.mask))
+						&& overlap.w > 0.0
+						&& overlap.h > 0.0
+				});
+
+				if blocked {
+					return None;
+				}
+
+				self.standing_height = None;
+
+				Some(standing)
+			}
+			_ => None,
+		}
+	}
+}
+
+/// Whether the object holds on to a climbable object; its center has to be in front of it
+fn is_ladder(collision: &CollisionInfo, center_x: f32) -> bool {
+	collision.collision == CollisionType::Climbable
+		&& collision.bounds.left() <= center_x
+		&& center_x <= collision.bounds.right()
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::actions::Action;
+	use crate::game_object::behaviour::controllable::{ControllableBehaviour, GroundState, MotionState};
+	use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult};
+	use crate::game_object::broadphase::Broadphase;
+	use crate::game_object::behaviour::collision::CollisionBehaviour;
+	use crate::game_object::{BodyType, BoundInfo, CollisionInfo, CollisionType, PhysicsVector};
+	use crate::input::{InputAxis, InputState};
+	use crate::math::bounds::Bounds;
+	use crate::serialization::script::ActionScript;
+	use crate::test_level::{input, player_bounds, script, TestLevel};
+
+	static DELTA_T: f64 = 1.0 / 60.0;
+
+	fn ground() -> Vec<CollisionInfo> {
+		vec![CollisionInfo {
+			id: 1,
+			bounds: FRect { x: 0.0, y: 100.0, w: 64.0, h: 0.0 },
+			body: BodyType::Static,
+			collision: CollisionType::Solid,
+			normal: PhysicsVector { x: 0.0, y: -1.0 },
+		}]
+	}
+
+	/// Something the object at x 0..64 overlaps or touches
+	fn touching(collision: CollisionType, bounds: FRect, normal: PhysicsVector) -> CollisionInfo {
+		CollisionInfo {
+			id: 2,
+			bounds,
+			body: BodyType::Static,
+			collision,
+			normal,
+		}
+	}
+
+	fn ladder() -> CollisionInfo {
+		touching(CollisionType::Climbable, FRect { x: 16.0, y: 0.0, w: 32.0, h: 64.0 }, PhysicsVector { x: 0.0, y: 1.0 })
+	}
+
+	/// A wall on the right of the object
+	fn wall() -> CollisionInfo {
+		touching(CollisionType::Solid, FRect { x: 64.0, y: 0.0, w: 0.0, h: 64.0 }, PhysicsVector { x: -1.0, y: 0.0 })
+	}
+
+	/// Ticks the behaviour for an object whose top is at `y`
+	fn tick(controllable: &mut ControllableBehaviour, y: f32, grounded: bool, jump: bool) -> BehaviourResult {
+		let collisions = if grounded { ground() } else { Vec::new() };
+		let actions: &[Action] = if jump { &[Action::Jump] } else { &[] };
+
+		tick_with(controllable, y, collisions, actions)
+	}
+
+	fn tick_with(controllable: &mut ControllableBehaviour, y: f32, collisions: Vec<CollisionInfo>, actions: &[Action]) -> BehaviourResult {
+		let mut input = InputState::default();
+
+		input.actions.extend(actions.iter().copied());
+
+		let broadphase = Broadphase::default();
+
+		controllable.tick(BehaviourParameter {
+			id: -1,
+			bounds: FRect { x: 0.0, y, w: 64.0, h: 64.0 },
+			input: &input,
+			world_bounds: FRect { x: 0.0, y: 0.0, w: 800.0, h: 600.0 },
+			broadphase: &broadphase,
+			collider: None,
+			collisions: &collisions,
+			force: None,
+			impulse: None,
+			motion: None,
+			speed_control: None,
+		}, DELTA_T)
+	}
+
+	fn jumped(result: &BehaviourResult) -> bool {
+		result.impulse.is_some_and(|impulse| impulse.y < 0.0)
+	}
+
+	#[test]
+	fn test_coyote_time() {
+		let mut controllable = ControllableBehaviour::new(5.0, 15.0);
+
+		tick(&mut controllable, 36.0, true, false);
+
+		// walked off the ledge a few ticks ago
+		tick(&mut controllable, 37.0, false, false);
+		tick(&mut controllable, 38.0, false, false);
+		assert!(jumped(&tick(&mut controllable, 39.0, false, true)));
+
+		// too late
+		let mut controllable = ControllableBehaviour::new(5.0, 15.0);
+
+		tick(&mut controllable, 36.0, true, false);
+
+		for i in 0..10 {
+			tick(&mut controllable, 37.0 + i as f32, false, false);
+		}
+
+		assert!(!jumped(&tick(&mut controllable, 50.0, false, true)));
+	}
+
+	#[test]
+	fn test_jump_buffer() {
+		let mut controllable = ControllableBehaviour::new(5.0, 15.0);
+
+		tick(&mut controllable, 20.0, false, false);
+		assert!(!jumped(&tick(&mut controllable, 25.0, false, true)));
+		tick(&mut controllable, 30.0, false, false);
+
+		// lands shortly after the jump was pressed
+		assert!(jumped(&tick(&mut controllable, 36.0, true, false)));
+
+		// holding the jump doesn't jump again on the next landing
+		let mut controllable = ControllableBehaviour::new(5.0, 15.0);
+
+		assert!(jumped(&tick(&mut controllable, 36.0, true, true)));
+
+		for _ in 0..20 {
+			tick(&mut controllable, 30.0, false, true);
+		}
+
+		assert!(!jumped(&tick(&mut controllable, 36.0, true, true)));
+	}
+
+	#[test]
+	fn test_jump_cut() {
+		let mut controllable = ControllableBehaviour::new(5.0, 15.0);
+
+		tick(&mut controllable, 36.0, true, true);
+		assert_eq!(controllable.state, GroundState::Jumping);
+
+		let result = tick(&mut controllable, 32.0, false, true);
+		assert_eq!(result.speed_control.unwrap().rise_factor, 1.0);
+
+		// released while rising
+		let result = tick(&mut controllable, 28.0, false, false);
+		assert_eq!(result.speed_control.unwrap().rise_factor, 0.5);
+		assert_eq!(controllable.state, GroundState::Airborne);
+
+		// only once
+		let result = tick(&mut controllable, 26.0, false, false);
+		assert_eq!(result.speed_control.unwrap().rise_factor, 1.0);
+	}
+
+	#[test]
+	fn test_defaults_match_new() {
+		let parsed: ControllableBehaviour = serde_json::from_str(r#"{ "speed": 5, "run_speed": 15 }"#).unwrap();
+		let new = ControllableBehaviour::new(5.0, 15.0);
+
+		assert_eq!(parsed.can_climb, new.can_climb);
+		assert_eq!(parsed.can_wall_slide, new.can_wall_slide);
+		assert_eq!(parsed.can_wall_jump, new.can_wall_jump);
+		assert_eq!(parsed.can_duck, new.can_duck);
+	}
+
+	#[test]
+	fn test_ladder() {
+		let mut controllable = ControllableBehaviour::new(5.0, 15.0);
+
+		// standing in front of a ladder doesn't hold on to it
+		let result = tick_with(&mut controllable, 0.0, vec![ladder()], &[]);
+		assert_eq!(result.speed_control.unwrap().vertical_speed, None);
+
+		let result = tick_with(&mut controllable, 0.0, vec![ladder()], &[Action::MoveUp]);
+		assert_eq!(controllable.state, GroundState::Climbing);
+		assert_eq!(result.motion, Some(MotionState::Climb));
+		assert_eq!(result.speed_control.unwrap().vertical_speed, Some(-3.0));
+
+		// letting go of the input stays on the ladder without falling
+		let result = tick_with(&mut controllable, 0.0, vec![ladder()], &[]);
+		assert_eq!(result.speed_control.unwrap().vertical_speed, Some(0.0));
+
+		// climbed off the top
+		let result = tick_with(&mut controllable, 0.0, Vec::new(), &[Action::MoveUp]);
+		assert_eq!(controllable.state, GroundState::Airborne);
+		assert_eq!(result.speed_control.unwrap().vertical_speed, None);
+
+		// jumping off the ladder
+		tick_with(&mut controllable, 0.0, vec![ladder()], &[Action::MoveDown]);
+		let result = tick_with(&mut controllable, 0.0, vec![ladder()], &[Action::Jump]);
+		assert!(jumped(&result));
+		assert_eq!(result.speed_control.unwrap().vertical_speed, None);
+
+		let mut controllable = ControllableBehaviour { can_climb: false, ..ControllableBehaviour::new(5.0, 15.0) };
+
+		let result = tick_with(&mut controllable, 0.0, vec![ladder()], &[Action::MoveUp]);
+		assert_eq!(result.speed_control.unwrap().vertical_speed, None);
+	}
+
+	#[test]
+	fn test_wall_slide_and_jump() {
+		let mut controllable = ControllableBehaviour::new(5.0, 15.0);
+
+		tick(&mut controllable, 0.0, false, false);
+
+		// falling next to the wall without pressing into it
+		let result = tick_with(&mut controllable, 10.0, vec![wall()], &[]);
+		assert_eq!(result.speed_control.unwrap().max_fall_speed, 20.0);
+
+		let result = tick_with(&mut controllable, 20.0, vec![wall()], &[Action::MoveRight]);
+		assert_eq!(result.motion, Some(MotionState::WallSlide));
+		assert_eq!(result.speed_control.unwrap().max_fall_speed, 2.0);
+
+		// jumps away from the wall
+		let result = tick_with(&mut controllable, 30.0, vec![wall()], &[Action::MoveRight, Action::Jump]);
+		assert_eq!(result.impulse, Some(PhysicsVector { x: -10.0, y: -15.0 }));
+		assert_eq!(controllable.state, GroundState::Jumping);
+
+		let mut controllable = ControllableBehaviour { can_wall_slide: false, can_wall_jump: false, ..ControllableBehaviour::new(5.0, 15.0) };
+
+		tick(&mut controllable, 0.0, false, false);
+
+		let result = tick_with(&mut controllable, 10.0, vec![wall()], &[Action::MoveRight, Action::Jump]);
+		assert_eq!(result.speed_control.unwrap().max_fall_speed, 20.0);
+		assert!(!jumped(&result));
+	}
+
+	/// Ticks an object on the ground at y 100 with a ceiling above it
+	fn tick_below(controllable: &mut ControllableBehaviour, bounds: FRect, ceiling: FRect, actions: &[Action]) -> BehaviourResult {
+		let mut input = InputState::default();
+
+		input.actions.extend(actions.iter().copied());
+
+		let broadphase = Broadphase::new(128.0, vec![BoundInfo {
+			id: 3,
+			bounds: ceiling,
+			mask: 0,
+			body: BodyType::Static,
+			collision: CollisionType::Solid,
+			collider: true,
+		}]);
+
+		controllable.tick(BehaviourParameter {
+			id: -1,
+			bounds,
+			input: &input,
+			world_bounds: FRect { x: 0.0, y: 0.0, w: 800.0, h: 600.0 },
+			broadphase: &broadphase,
+			collider: Some(CollisionBehaviour::new()),
+			collisions: &ground(),
+			force: None,
+			impulse: None,
+			motion: None,
+			speed_control: None,
+		}, DELTA_T)
+	}
+
+	#[test]
+	fn test_duck() {
+		let mut controllable = ControllableBehaviour { duck_texture: Some(7), ..ControllableBehaviour::new(5.0, 15.0) };
+
+		let standing = FRect { x: 0.0, y: 36.0, w: 64.0, h: 64.0 };
+		let ducking = FRect { x: 0.0, y: 68.0, w: 64.0, h: 32.0 };
+		let high_ceiling = FRect { x: 0.0, y: 0.0, w: 64.0, h: 36.0 };
+		let low_ceiling = FRect { x: 0.0, y: 0.0, w: 64.0, h: 50.0 };
+
+		// shrinks towards the feet and moves slower
+		let result = tick_below(&mut controllable, standing, high_ceiling, &[Action::Duck, Action::MoveRight]);
+		assert_eq!(result.bounds, Some(ducking));
+		assert_eq!(result.motion, Some(MotionState::Duck));
+		assert_eq!(result.texture, Some(7));
+		assert_eq!(result.force.unwrap().x, 2.5);
+
+		let result = tick_below(&mut controllable, ducking, high_ceiling, &[Action::Duck]);
+		assert_eq!(result.bounds, None);
+
+		// no room to stand up
+		let result = tick_below(&mut controllable, ducking, low_ceiling, &[]);
+		assert_eq!(result.bounds, None);
+		assert_eq!(result.motion, Some(MotionState::Duck));
+
+		let result = tick_below(&mut controllable, ducking, high_ceiling, &[]);
+		assert_eq!(result.bounds, Some(standing));
+		assert_eq!(result.motion, Some(MotionState::Idle));
+		assert_eq!(result.texture, None);
+	}
+
+	#[test]
+	fn test_analog_input_scales_speed() {
+		let mut half = InputState::default();
+		half.set_axis(InputAxis::MoveX, 0.5, 0.0);
+
+		let mut full_runner = TestLevel::new().runner();
+		let mut half_runner = TestLevel::new().runner();
+
+		full_runner.run(60, &ActionScript::default());
+		half_runner.run(60, &ActionScript::default());
+
+		let start = player_bounds(&full_runner).x;
+
+		full_runner.run(30, &script([(100, input([Action::MoveRight]))]));
+		half_runner.run(30, &script([(100, half)]));
+
+		let full_distance = player_bounds(&full_runner).x - start;
+		let half_distance = player_bounds(&half_runner).x - start;
+
+		assert!(half_distance > 0.0);
+		assert!(half_distance < full_distance);
+	}
+
+	#[test]
+	fn test_max_fall_speed() {
+		let mut runner = TestLevel::new()
+			.start_y(0.0)
+			.controllable(r#"{ "speed": 5, "run_speed": 15, "max_fall_speed": 5 }"#)
+			.runner();
+
+		runner.run(59, &ActionScript::default());
+		let before = player_bounds(&runner).y;
+
+		runner.run(1, &ActionScript::default());
+		let after = player_bounds(&runner).y;
+
+		// 5 meters per second at 32 pixels per meter and 60 ticks per second
+		assert!((after - before - 5.0 * 32.0 / 60.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn test_climb_ladder() {
+		let mut runner = TestLevel::new()
+			.start_y(516.0)
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 110, "y": 300, "w": 40, "h": 280 },
+				"collision": "Climbable",
+				"drawable": { "z": { "Background": 1 } },
+				"behaviours": [{ "type": "Collision" }]
+			}"#)
+			.runner();
+
+		let script = script([(60, input([Action::MoveUp]))]);
+
+		// 3 meters per second for a second
+		runner.run(60, &script);
+		assert!((player_bounds(&runner).bottom() - 484.0).abs() < 0.1);
+
+		// holds on without falling
+		runner.run(30, &script);
+		assert!((player_bounds(&runner).bottom() - 484.0).abs() < 0.1);
+	}
+
+	#[test]
+	fn test_duck_under_ceiling() {
+		// 40 pixels of room between the ground and the ceiling
+		let mut runner = TestLevel::new()
+			.start_y(516.0)
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 180, "y": 520, "w": 220, "h": 20 },
+				"drawable": { "z": { "Foreground": 1 } },
+				"behaviours": [{ "type": "Collision" }]
+			}"#)
+			.runner();
+
+		let script = script([
+			(10, input([Action::Duck])),
+			(100, input([Action::Duck, Action::MoveRight])),
+		]);
+
+		// crawls under the ceiling
+		runner.run(110, &script);
+		assert_eq!(player_bounds(&runner).h, 32.0);
+		assert!(player_bounds(&runner).right() > 200.0);
+		assert!((player_bounds(&runner).bottom() - 580.0).abs() < 0.01);
+
+		// can't stand up below it
+		runner.run(10, &script);
+		assert_eq!(player_bounds(&runner).h, 32.0);
+	}
+}
@@ -0,0 +1,31 @@
+use crate::game_object::behaviour::_ser_optional_rect;
+use crate::game_object::behaviour::_de_optional_rect;
+use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult, WorldCommand};
+use crate::math::bounds::Bounds;
+use sdl3::render::FRect;
+use serde::{Deserialize, Serialize};
+
+/// Removes the object from the world once it has left an area
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct DespawnBehaviour {
+	/// the area the object has to stay in; the world bounds if not set
+	#[serde(default, deserialize_with = "_de_optional_rect", serialize_with = "_ser_optional_rect")]
+	pub bounds: Option<FRect>,
+}
+
+impl DespawnBehaviour {
+	pub fn tick(&mut self, params: BehaviourParameter, _: f64) -> BehaviourResult {
+		let area = self.bounds.unwrap_or(params.world_bounds);
+
+		let commands = if params.bounds.intersects(area) {
+			Vec::new()
+		} else {
+			vec![WorldCommand::Despawn(params.id)]
+		};
+
+		BehaviourResult {
+			commands,
+			..Default::default()
+		}
+	}
+}
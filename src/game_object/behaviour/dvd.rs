@@ -4,30 +4,27 @@ use crate::game_object::PhysicsVector;
 use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult, BehaviourSpeed};
 use crate::math::bounds::Bounds;
 use sdl3::render::FRect;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
+use crate::util::Random;
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 pub struct DvdBehaviour {
 	#[serde(default, deserialize_with = "_de_optional_rect", serialize_with = "_ser_optional_rect")]
     pub bounds: Option<FRect>,
-	#[serde(deserialize_with = "_de_behavior_speed")]
-    pub speed: PhysicsVector,
-}
-
-fn _de_behavior_speed<'de, D>(deserializer: D) -> Result<PhysicsVector, D::Error> where D: Deserializer<'de>
-{
-	let res = BehaviourSpeed::deserialize(deserializer);
-
-	match res {
-		Ok(speed) => Ok(PhysicsVector::from(speed)),
-		Err(err) => Err(err)
-	}
+    pub speed: BehaviourSpeed,
+	/// the current velocity, resolved from `speed` when the object is added to a world
+	#[serde(skip_deserializing)]
+	pub velocity: PhysicsVector,
 }
 
 impl DvdBehaviour {
     pub fn new(bounds: FRect, speed: PhysicsVector) -> Self {
-        Self { bounds: Some(bounds), speed }
+        Self { bounds: Some(bounds), speed: BehaviourSpeed::from(speed), velocity: speed }
     }
+
+	pub fn init(&mut self, random: &mut Random) {
+		self.velocity = self.speed.resolve(random);
+	}
 }
 
 impl DvdBehaviour {
@@ -36,18 +33,18 @@ impl DvdBehaviour {
         let mut position = PhysicsVector::from(center);
 		let clamp_bounds = self.bounds.unwrap_or(params.world_bounds);
 
-		position += self.speed * delta_t as f32;
+		position += self.velocity * delta_t as f32;
 
         if position.x < clamp_bounds.left() {
-            self.speed.x = -self.speed.x;
+            self.velocity.x = -self.velocity.x;
         } else if position.x > clamp_bounds.right() {
-            self.speed.x = -self.speed.x;
+            self.velocity.x = -self.velocity.x;
         }
 
         if position.y < clamp_bounds.top() {
-            self.speed.y = -self.speed.y;
+            self.velocity.y = -self.velocity.y;
         } else if position.y > clamp_bounds.bottom() {
-            self.speed.y = -self.speed.y;
+            self.velocity.y = -self.velocity.y;
         }
 
 		position.x = position.x.clamp(clamp_bounds.left(), clamp_bounds.right());
@@ -59,9 +56,7 @@ impl DvdBehaviour {
 
         BehaviourResult {
             bounds: Some(bounds),
-            collisions: None,
-			force: None,
-			impulse: None,
+            ..Default::default()
         }
     }
 }
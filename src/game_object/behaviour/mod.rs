@@ -1,20 +1,33 @@
+pub mod animation;
+pub mod camera_shake;
+pub mod camera_zone;
 pub mod collision;
 pub mod controllable;
+pub mod despawn;
 pub mod dvd;
 pub mod physics;
+pub mod platform;
+pub mod spawner;
 
-use std::collections::HashSet;
 use crate::serialization::{AssetBounds, AssetId};
-use sdl3::render::FRect;
+use sdl3::render::{FPoint, FRect};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::ser::SerializeMap;
-use crate::actions::Action;
+use crate::input::InputState;
+use crate::game_object::behaviour::animation::AnimationBehaviour;
+use crate::game_object::behaviour::camera_shake::CameraShakeBehaviour;
+use crate::game_object::behaviour::camera_zone::CameraZoneBehaviour;
 use crate::game_object::behaviour::collision::CollisionBehaviour;
-use crate::game_object::behaviour::controllable::ControllableBehaviour;
+use crate::game_object::behaviour::controllable::{ControllableBehaviour, MotionState, SpeedControl};
+use crate::game_object::behaviour::despawn::DespawnBehaviour;
 use crate::game_object::behaviour::dvd::DvdBehaviour;
 use crate::game_object::behaviour::physics::PhysicsBehaviour;
-use crate::game_object::{BoundInfo, CollisionInfo, PhysicsVector};
-use crate::util::random;
+use crate::game_object::behaviour::platform::PlatformBehaviour;
+use crate::game_object::behaviour::spawner::SpawnerBehaviour;
+use crate::game_object::broadphase::Broadphase;
+use crate::game_object::camera::CameraCommand;
+use crate::game_object::{CollisionInfo, PhysicsVector};
+use crate::util::Random;
 
 fn _de_optional_rect<'de, D>(deserializer: D) -> Result<Option<FRect>, D::Error> where D: Deserializer<'de>
 {
@@ -47,13 +60,13 @@ pub enum BehaviourSpeed {
 	},
 }
 
-impl From<BehaviourSpeed> for PhysicsVector {
-	fn from(value: BehaviourSpeed) -> Self {
-		match value {
+impl BehaviourSpeed {
+	pub fn resolve(&self, random: &mut Random) -> PhysicsVector {
+		match *self {
 			BehaviourSpeed::Fixed(v) => v,
-			BehaviourSpeed::Random { min, max } => Self {
-				x: random(min.x, max.x),
-				y: random(min.y, max.y),
+			BehaviourSpeed::Random { min, max } => PhysicsVector {
+				x: random.range(min.x, max.x),
+				y: random.range(min.y, max.y),
 			},
 		}
 	}
@@ -69,33 +82,71 @@ impl From<PhysicsVector> for BehaviourSpeed {
 pub struct BehaviourParameter<'a> {
     pub id: AssetId,
     pub bounds: FRect,
-    pub actions: &'a HashSet<Action>,
+    pub input: &'a InputState,
 	pub world_bounds: FRect,
-    pub other_bounds: &'a Vec<BoundInfo>,
+	/// every object in the world at the start of the tick; answers area queries and casts
+	pub broadphase: &'a Broadphase,
+	/// the collision behaviour of the object, if it has one
+	pub collider: Option<CollisionBehaviour>,
 	pub collisions: &'a Vec<CollisionInfo>,
 	pub force: Option<PhysicsVector>,
 	pub impulse: Option<PhysicsVector>,
+	/// what a controllable behaviour before this one is doing
+	pub motion: Option<MotionState>,
+	pub speed_control: Option<SpeedControl>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct BehaviourResult {
     pub bounds: Option<FRect>,
     pub collisions: Option<Vec<CollisionInfo>>,
 	pub force: Option<PhysicsVector>,
 	pub impulse: Option<PhysicsVector>,
+	pub commands: Vec<WorldCommand>,
+	pub motion: Option<MotionState>,
+	/// frame of the sprite sheet the drawable shows
+	pub frame: Option<usize>,
+	/// texture the drawable shows instead of its own in this tick, e.g. while ducking
+	pub texture: Option<AssetId>,
+	pub speed_control: Option<SpeedControl>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+/// Changes to the world that get applied after every object has been ticked
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorldCommand {
+	/// adds a copy of a template of the level, centered on `position`
+	Spawn {
+		template: String,
+		position: FPoint,
+	},
+	Despawn(i32),
+	Camera(CameraCommand),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum BehaviourType {
 	Dvd(DvdBehaviour),
 	Controllable(ControllableBehaviour),
 	Collision(CollisionBehaviour),
-	Physics(PhysicsBehaviour)
+	Physics(PhysicsBehaviour),
+	Despawn(DespawnBehaviour),
+	Spawner(SpawnerBehaviour),
+	CameraShake(CameraShakeBehaviour),
+	CameraZone(CameraZoneBehaviour),
+	Animation(AnimationBehaviour),
+	Platform(PlatformBehaviour),
 }
 
 
 impl BehaviourType {
+	/// Resolves everything that is drawn at random once the object is added to a world
+	pub fn init(&mut self, random: &mut Random) {
+		if let BehaviourType::Dvd(behavior) = self {
+			behavior.init(random)
+		}
+	}
+
 	pub fn tick(&mut self, params: BehaviourParameter, delta_t: f64) -> BehaviourResult
 	{
 		match self {
@@ -111,6 +162,24 @@ impl BehaviourType {
 			BehaviourType::Collision(behavior) => {
 				behavior.tick(params, delta_t)
 			}
+			BehaviourType::Despawn(behavior) => {
+				behavior.tick(params, delta_t)
+			}
+			BehaviourType::Spawner(behavior) => {
+				behavior.tick(params, delta_t)
+			}
+			BehaviourType::CameraShake(behavior) => {
+				behavior.tick(params, delta_t)
+			}
+			BehaviourType::CameraZone(behavior) => {
+				behavior.tick(params, delta_t)
+			}
+			BehaviourType::Animation(behavior) => {
+				behavior.tick(params, delta_t)
+			}
+			BehaviourType::Platform(behavior) => {
+				behavior.tick(params, delta_t)
+			}
 		}
 	}
 }
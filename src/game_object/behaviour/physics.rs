@@ -1,6 +1,7 @@
 use crate::game_object::behaviour::_ser_optional_rect;
 use crate::game_object::behaviour::_de_optional_rect;
-use crate::game_object::PhysicsVector;
+use crate::game_object::behaviour::collision::sweep;
+use crate::game_object::{BodyType, BoundInfo, CollisionInfo, CollisionType, PhysicsVector};
 use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult};
 use crate::math::bounds::Bounds;
 use sdl3::render::FRect;
@@ -9,7 +10,6 @@ use serde::{Deserialize, Serialize};
 use crate::math::VectorOps;
 
 static PIXELS_PER_METER: f32 = 32.0;
-static METERS_PER_PIXEL: f32 = 1.0/PIXELS_PER_METER;
 
 static GRAVITY: PhysicsVector = PhysicsVector {
 	x: 0.0,
@@ -18,6 +18,9 @@ static GRAVITY: PhysicsVector = PhysicsVector {
 
 static AIR_RESISTANCE_COEF: f32 = 0.01;
 
+/// How often a body can hit something and slide on within a single tick
+static MAX_SWEEPS: usize = 3;
+
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 pub struct PhysicsBehaviour {
 	#[serde(default, deserialize_with = "_de_optional_rect", serialize_with = "_ser_optional_rect")]
@@ -35,9 +38,7 @@ impl PhysicsBehaviour {
 impl PhysicsBehaviour {
 	pub fn tick(&mut self, params: BehaviourParameter, delta_t: f64) -> BehaviourResult {
         let sec = delta_t as f32;
-        let center = params.bounds.center();
-        let mut position = PhysicsVector::from(center) * METERS_PER_PIXEL;
-		let speed_magnitude = self.speed.len();
+		let speed_magnitude = self.speed.length();
 		let speed_anti_normal = -self.speed.normal();
 
 		let impulse = if let Some(impulse) = params.impulse { impulse / self.mass } else { PhysicsVector::default() };
@@ -51,47 +52,135 @@ impl PhysicsBehaviour {
 		self.speed += impulse;
 		self.speed += acceleration * sec;
 
-		for i in 0..params.collisions.len() {
-			let (_, collision, _) = params.collisions[i];
-			let col_center = collision.center();
+		if let Some(control) = params.speed_control {
+			if self.speed.y < 0.0 {
+				self.speed.y *= control.rise_factor;
+			}
 
-			if collision.w > collision.h {
-				let pos_sign = (col_center.y - center.y).signum();
-				let speed_sign = self.speed.y.signum();
+			self.speed.y = f32::min(self.speed.y, control.max_fall_speed);
 
-				if pos_sign == speed_sign {
-					// cancel movement in the direction of the collision
-					self.speed.y = 0.0;
-				}
-			} else {
-				let pos_sign = (col_center.x - center.x).signum();
-				let speed_sign = self.speed.x.signum();
+			if let Some(vertical_speed) = control.vertical_speed {
+				self.speed.y = vertical_speed;
+			}
+		}
 
-				if pos_sign == speed_sign {
-					// cancel movement in the direction of the collision
-					self.speed.x = 0.0;
-				}
+		for i in 0..params.collisions.len() {
+			if params.collisions[i].is_solid() {
+				self.cancel_towards(params.collisions[i].normal);
 			}
 		}
 
-		position += self.speed * sec;
+		let mut bounds = params.bounds;
+		let mut motion = self.speed * (sec * PIXELS_PER_METER);
+		let mut collisions = params.collisions.clone();
+
+		if let Some(collider) = params.collider {
+			let target = FRect { x: bounds.x + motion.x, y: bounds.y + motion.y, ..bounds };
+
+			let dropping = collider.is_dropping(params.input);
+
+			// dynamic bodies push each other out during the resolution instead
+			let candidates: Vec<&BoundInfo> = params
+				.broadphase
+				.query_rect(bounds.join(target))
+				.into_iter()
+				.filter(|other| {
+					other.id != params.id
+						&& other.collider
+						&& other.body != BodyType::Dynamic
+						&& collider.collides_with(other.mask)
+						&& match other.collision {
+							CollisionType::Solid => true,
+							CollisionType::OneWay => !dropping,
+							CollisionType::Trigger | CollisionType::Climbable => false,
+						}
+				})
+				.collect();
+
+			for _ in 0..MAX_SWEEPS {
+				let hit = candidates
+					.iter()
+					.filter_map(|other| sweep(bounds, motion, other.bounds).map(|(time, normal)| (time, normal, *other)))
+					// one-way platforms are only hit from above
+					.filter(|(_, normal, other)| other.collision != CollisionType::OneWay || normal.y < 0.0)
+					.min_by(|a, b| a.0.total_cmp(&b.0));
+
+				let Some((time, normal, other)) = hit else {
+					break;
+				};
+
+				// stop at the time of impact and slide along the surface with the rest of the motion
+				bounds.x += motion.x * time;
+				bounds.y += motion.y * time;
+				motion *= 1.0 - time;
+
+				let towards = motion.dot(normal);
+
+				if towards < 0.0 {
+					motion -= normal * towards;
+				}
 
-		position *= PIXELS_PER_METER;
+				self.cancel_towards(normal);
 
+				collisions.push(CollisionInfo {
+					id: other.id,
+					bounds: other.bounds.intersection(bounds),
+					body: other.body,
+					collision: other.collision,
+					normal,
+				});
+			}
+		}
+
+		bounds.x += motion.x;
+		bounds.y += motion.y;
+
+		let mut position = PhysicsVector::from(bounds.center());
 		let clamp_bounds = self.bounds.unwrap_or(params.world_bounds);
 
 		position.x = position.x.clamp(clamp_bounds.left(), clamp_bounds.right());
 		position.y = position.y.clamp(clamp_bounds.top(), clamp_bounds.bottom());
 
-        let mut bounds = params.bounds;
-
         bounds.set_center(position);
 
         BehaviourResult {
             bounds: Some(bounds),
-            collisions: None,
-			force: None,
-			impulse: None,
+            collisions: Some(collisions),
+            ..Default::default()
         }
     }
+
+	/// Removes the part of the speed that moves into a surface with the given normal
+	fn cancel_towards(&mut self, normal: PhysicsVector) {
+		let towards = self.speed.dot(normal);
+
+		if towards < 0.0 {
+			self.speed -= normal * towards;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::math::bounds::Bounds;
+	use crate::serialization::script::ActionScript;
+	use crate::test_level::{player_bounds, TestLevel};
+
+	#[test]
+	fn test_thin_platform_at_low_tick_rate() {
+		// a quarter of a second per tick moves the player further than the platform is thick
+		let mut runner = TestLevel::new()
+			.tick_rate(4)
+			.start_y(0.0)
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 0, "y": 520, "w": 800, "h": 2 },
+				"behaviours": [{ "type": "Collision" }]
+			}"#)
+			.runner();
+
+		runner.run(20, &ActionScript::default());
+
+		assert!((player_bounds(&runner).bottom() - 520.0).abs() < 0.01);
+	}
 }
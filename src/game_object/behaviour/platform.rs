@@ -0,0 +1,160 @@
+use crate::game_object::PhysicsVector;
+use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult};
+use crate::math::VectorOps;
+use serde::{Deserialize, Serialize};
+
+/// What a platform does once it has reached the last waypoint
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathMode {
+	/// goes back through the waypoints in reverse
+	#[default]
+	PingPong,
+	/// moves on to the first waypoint
+	Loop,
+	/// stops
+	Once,
+}
+
+/// Moves the object along a path; bodies standing on it are carried along by the world
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PlatformBehaviour {
+	/// positions of the top left corner of the object
+	pub waypoints: Vec<PhysicsVector>,
+	/// pixels per second
+	pub speed: f32,
+	#[serde(default)]
+	pub mode: PathMode,
+	/// index of the waypoint the platform moves to
+	#[serde(default)]
+	pub target: usize,
+	/// whether a ping-pong path is on its way back
+	#[serde(default)]
+	pub backwards: bool,
+}
+
+impl PlatformBehaviour {
+	/// Picks the next waypoint; false once a one-shot path has reached its end
+	fn advance(&mut self) -> bool {
+		let last = self.waypoints.len().saturating_sub(1);
+
+		match self.mode {
+			PathMode::Loop => self.target = (self.target + 1) % self.waypoints.len(),
+			PathMode::Once if self.target >= last => return false,
+			PathMode::Once => self.target += 1,
+			PathMode::PingPong => {
+				if (self.backwards && self.target == 0) || (!self.backwards && self.target >= last) {
+					self.backwards = !self.backwards;
+				}
+
+				self.target = if self.backwards {
+					self.target.saturating_sub(1)
+				} else {
+					usize::min(self.target + 1, last)
+				};
+			}
+		}
+
+		true
+	}
+
+	pub fn tick(&mut self, params: BehaviourParameter, delta_t: f64) -> BehaviourResult {
+		let mut position = PhysicsVector { x: params.bounds.x, y: params.bounds.y };
+		let mut distance = self.speed * delta_t as f32;
+
+		// every waypoint can be passed at most twice per tick, even if they are all at the same spot
+		for _ in 0..self.waypoints.len() * 2 {
+			let Some(target) = self.waypoints.get(self.target).copied() else {
+				break;
+			};
+
+			let to_target = target - position;
+			let length = to_target.length();
+
+			if length > distance {
+				position += to_target * (distance / length);
+				break;
+			}
+
+			position = target;
+			distance -= length;
+
+			if !self.advance() {
+				break;
+			}
+		}
+
+		let mut bounds = params.bounds;
+
+		bounds.x = position.x;
+		bounds.y = position.y;
+
+		BehaviourResult {
+			bounds: Some(bounds),
+			..Default::default()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::game_object::behaviour::platform::{PathMode, PlatformBehaviour};
+	use crate::game_object::PhysicsVector;
+	use crate::math::bounds::Bounds;
+	use crate::serialization::script::ActionScript;
+	use crate::test_level::{player_bounds, TestLevel};
+
+	fn platform(mode: PathMode) -> PlatformBehaviour {
+		PlatformBehaviour {
+			waypoints: vec![
+				PhysicsVector { x: 0.0, y: 0.0 },
+				PhysicsVector { x: 100.0, y: 0.0 },
+				PhysicsVector { x: 100.0, y: 50.0 },
+			],
+			speed: 10.0,
+			mode,
+			target: 0,
+			backwards: false,
+		}
+	}
+
+	fn targets(mut platform: PlatformBehaviour, steps: usize) -> Vec<usize> {
+		(0..steps)
+			.map_while(|_| platform.advance().then_some(platform.target))
+			.collect()
+	}
+
+	#[test]
+	fn test_path_modes() {
+		assert_eq!(targets(platform(PathMode::PingPong), 6), vec![1, 2, 1, 0, 1, 2]);
+		assert_eq!(targets(platform(PathMode::Loop), 5), vec![1, 2, 0, 1, 2]);
+		assert_eq!(targets(platform(PathMode::Once), 5), vec![1, 2]);
+	}
+
+	#[test]
+	fn test_platform_carries_player() {
+		// the player starts on top of the platform
+		let mut runner = TestLevel::new()
+			.start_y(336.0)
+			.object(r#"{
+				"id": 2,
+				"bounds": { "x": 0, "y": 400, "w": 200, "h": 20 },
+				"drawable": { "z": { "Foreground": 1 } },
+				"behaviours": [
+					{
+						"type": "Platform",
+						"waypoints": [{ "x": 0, "y": 400 }, { "x": 300, "y": 400 }],
+						"speed": 60
+					},
+					{ "type": "Collision" }
+				]
+			}"#)
+			.runner();
+
+		runner.run(60, &ActionScript::default());
+
+		let bounds = player_bounds(&runner);
+
+		assert!((bounds.x - 160.0).abs() < 0.1);
+		assert!((bounds.bottom() - 400.0).abs() < 0.01);
+	}
+}
@@ -0,0 +1,43 @@
+use crate::game_object::behaviour::{BehaviourParameter, BehaviourResult, WorldCommand};
+use crate::math::bounds::Bounds;
+use serde::{Deserialize, Serialize};
+
+/// Spawns copies of a template of the level at the center of the object
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SpawnerBehaviour {
+	pub template: String,
+	/// seconds between two spawns
+	pub interval: f32,
+	/// how many objects get spawned at most; no limit if not set
+	#[serde(default)]
+	pub limit: Option<u32>,
+	#[serde(default)]
+	pub spawned: u32,
+	#[serde(default)]
+	pub timer: f32,
+}
+
+impl SpawnerBehaviour {
+	pub fn tick(&mut self, params: BehaviourParameter, delta_t: f64) -> BehaviourResult {
+		let mut commands = Vec::new();
+
+		self.timer += delta_t as f32;
+
+		let exhausted = self.limit.is_some_and(|limit| self.spawned >= limit);
+
+		if self.timer >= self.interval && !exhausted {
+			self.timer -= self.interval;
+			self.spawned += 1;
+
+			commands.push(WorldCommand::Spawn {
+				template: self.template.clone(),
+				position: params.bounds.center(),
+			});
+		}
+
+		BehaviourResult {
+			commands,
+			..Default::default()
+		}
+	}
+}
@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+use sdl3::render::{FPoint, FRect};
+use crate::game_object::behaviour::collision::sweep;
+use crate::game_object::{BodyType, BoundInfo, CollisionType, ObjectMask, PhysicsVector};
+use crate::math::bounds::Bounds;
+use crate::math::VectorOps;
+use crate::util::Random;
+
+type Cell = (i32, i32);
+
+/// The first object a ray or box cast runs into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CastHit {
+	pub id: i32,
+	/// where the ray hits; for box casts the center of the box at the time of impact
+	pub point: FPoint,
+	/// points away from the hit object
+	pub normal: PhysicsVector,
+	/// from the origin of the cast to `point`
+	pub distance: f32,
+}
+
+/// Uniform grid over the bounds of all objects of a tick; every object is stored in all cells
+/// it overlaps. Query results are in the order the objects were added.
+#[derive(Debug, Default)]
+pub struct Broadphase {
+	cell_size: f32,
+	objects: Vec<BoundInfo>,
+	cells: HashMap<Cell, Vec<usize>>,
+	min_cell: Cell,
+	max_cell: Cell,
+}
+
+impl Broadphase {
+	pub fn new(cell_size: f32, objects: Vec<BoundInfo>) -> Self {
+		let mut broadphase = Self {
+			cell_size,
+			objects: Vec::new(),
+			cells: HashMap::new(),
+			min_cell: (i32::MAX, i32::MAX),
+			max_cell: (i32::MIN, i32::MIN),
+		};
+
+		for object in objects {
+			broadphase.insert(object);
+		}
+
+		broadphase
+	}
+
+	fn insert(&mut self, object: BoundInfo) {
+		let index = self.objects.len();
+		let (min, max) = self.cell_range(object.bounds);
+
+		for x in min.0..=max.0 {
+			for y in min.1..=max.1 {
+				self.cells.entry((x, y)).or_default().push(index);
+			}
+		}
+
+		self.min_cell = (i32::min(self.min_cell.0, min.0), i32::min(self.min_cell.1, min.1));
+		self.max_cell = (i32::max(self.max_cell.0, max.0), i32::max(self.max_cell.1, max.1));
+		self.objects.push(object);
+	}
+
+	/// Moves the objects whose bounds changed to the cells they overlap now, instead of building
+	/// the grid again; when objects were added or removed, it is built again
+	pub fn update(&mut self, objects: Vec<BoundInfo>) {
+		let same_objects = objects.len() == self.objects.len()
+			&& objects.iter().zip(&self.objects).all(|(a, b)| a.id == b.id);
+
+		if !same_objects {
+			*self = Self::new(self.cell_size, objects);
+			return;
+		}
+
+		for (index, object) in objects.into_iter().enumerate() {
+			let (old_min, old_max) = self.cell_range(self.objects[index].bounds);
+			let (min, max) = self.cell_range(object.bounds);
+
+			self.objects[index] = object;
+
+			if (old_min, old_max) == (min, max) {
+				continue;
+			}
+
+			for x in old_min.0..=old_max.0 {
+				for y in old_min.1..=old_max.1 {
+					let Some(cell) = self.cells.get_mut(&(x, y)) else {
+						continue;
+					};
+
+					cell.retain(|&i| i != index);
+
+					if cell.is_empty() {
+						self.cells.remove(&(x, y));
+					}
+				}
+			}
+
+			for x in min.0..=max.0 {
+				for y in min.1..=max.1 {
+					self.cells.entry((x, y)).or_default().push(index);
+				}
+			}
+
+			// the range only grows; cells outside of it are empty, so queries stay correct
+			self.min_cell = (i32::min(self.min_cell.0, min.0), i32::min(self.min_cell.1, min.1));
+			self.max_cell = (i32::max(self.max_cell.0, max.0), i32::max(self.max_cell.1, max.1));
+		}
+	}
+
+	fn cell(&self, x: f32, y: f32) -> Cell {
+		((x / self.cell_size).floor() as i32, (y / self.cell_size).floor() as i32)
+	}
+
+	fn cell_range(&self, rect: FRect) -> (Cell, Cell) {
+		(self.cell(rect.left(), rect.top()), self.cell(rect.right(), rect.bottom()))
+	}
+
+	/// Indices of all objects stored in the given cells, sorted and without duplicates
+	fn candidates(&self, min: Cell, max: Cell) -> Vec<usize> {
+		let min = (i32::max(min.0, self.min_cell.0), i32::max(min.1, self.min_cell.1));
+		let max = (i32::min(max.0, self.max_cell.0), i32::min(max.1, self.max_cell.1));
+
+		let mut indices = Vec::new();
+
+		for x in min.0..=max.0 {
+			for y in min.1..=max.1 {
+				if let Some(cell) = self.cells.get(&(x, y)) {
+					indices.extend_from_slice(cell);
+				}
+			}
+		}
+
+		indices.sort_unstable();
+		indices.dedup();
+		indices
+	}
+
+	/// All objects intersecting `rect`, including ones that only touch it
+	pub fn query_rect(&self, rect: FRect) -> Vec<&BoundInfo> {
+		let (min, max) = self.cell_range(rect);
+
+		self.candidates(min, max)
+			.into_iter()
+			.map(|i| &self.objects[i])
+			.filter(|o| o.bounds.intersects(rect))
+			.collect()
+	}
+
+	/// All objects containing `point`
+	pub fn query_point(&self, point: FPoint) -> Vec<&BoundInfo> {
+		let cell = self.cell(point.x, point.y);
+
+		self.candidates(cell, cell)
+			.into_iter()
+			.map(|i| &self.objects[i])
+			.filter(|o| o.bounds.contains(point))
+			.collect()
+	}
+
+	/// The object closest to `point` that passes `filter`; the distance to objects containing
+	/// the point is 0.0
+	pub fn nearest<F>(&self, point: FPoint, filter: F) -> Option<&BoundInfo>
+	where
+		F: Fn(&BoundInfo) -> bool,
+	{
+		if self.objects.is_empty() {
+			return None;
+		}
+
+		// rings around a cell outside of the grid would be empty until they reach it; every ring
+		// around the closest cell inside it is still at least as far from the point
+		let (x, y) = self.cell(point.x, point.y);
+		let center = (x.clamp(self.min_cell.0, self.max_cell.0), y.clamp(self.min_cell.1, self.max_cell.1));
+		// at most the size of the grid
+		let max_ring = [
+			center.0 - self.min_cell.0,
+			self.max_cell.0 - center.0,
+			center.1 - self.min_cell.1,
+			self.max_cell.1 - center.1,
+		]
+		.into_iter()
+		.max()
+		.unwrap_or(0)
+		.max(0);
+
+		let mut nearest: Option<(f32, usize)> = None;
+
+		for ring in 0..=max_ring {
+			// everything in this ring is at least this far away
+			let min_distance = (ring - 1).max(0) as f32 * self.cell_size;
+
+			if let Some((distance, _)) = nearest
+				&& distance < min_distance
+			{
+				break;
+			}
+
+			for index in self.ring(center, ring) {
+				let object = &self.objects[index];
+
+				if !filter(object) {
+					continue;
+				}
+
+				let distance = distance(object.bounds, point);
+
+				// ties go to the object added first
+				let closer = match nearest {
+					Some((best, best_index)) => distance < best || (distance == best && index < best_index),
+					None => true,
+				};
+
+				if closer {
+					nearest = Some((distance, index));
+				}
+			}
+		}
+
+		nearest.map(|(_, i)| &self.objects[i])
+	}
+
+	/// Casts a ray from `origin` along `direction`; objects that contain the origin, don't match
+	/// `mask` or have the id `ignore` are not hit
+	pub fn raycast(
+		&self,
+		origin: FPoint,
+		direction: PhysicsVector,
+		max_distance: f32,
+		mask: ObjectMask,
+		ignore: Option<i32>,
+	) -> Option<CastHit> {
+		let bounds = FRect { x: origin.x, y: origin.y, w: 0.0, h: 0.0 };
+
+		self.boxcast(bounds, direction, max_distance, mask, ignore)
+	}
+
+	/// Moves `bounds` along `direction` and returns the first object it touches; objects it
+	/// already overlaps, that don't match `mask` or have the id `ignore` are not hit
+	pub fn boxcast(
+		&self,
+		bounds: FRect,
+		direction: PhysicsVector,
+		max_distance: f32,
+		mask: ObjectMask,
+		ignore: Option<i32>,
+	) -> Option<CastHit> {
+		if direction.length() == 0.0 || max_distance <= 0.0 {
+			return None;
+		}
+
+		let direction = direction.normal();
+		let motion = direction * max_distance;
+		let target = FRect { x: bounds.x + motion.x, y: bounds.y + motion.y, ..bounds };
+
+		let (time, normal, other) = self
+			.query_rect(bounds.join(target))
+			.into_iter()
+			.filter(|other| Some(other.id) != ignore && (mask == 0 || other.mask == 0 || other.mask & mask != 0))
+			.filter_map(|other| sweep(bounds, motion, other.bounds).map(|(time, normal)| (time, normal, other)))
+			.min_by(|a, b| a.0.total_cmp(&b.0))?;
+
+		let distance = time * max_distance;
+		let center = bounds.center();
+
+		Some(CastHit {
+			id: other.id,
+			point: FPoint::new(center.x + direction.x * distance, center.y + direction.y * distance),
+			normal,
+			distance,
+		})
+	}
+
+	/// Indices of the objects in the cells at exactly `ring` cells distance from `center`
+	fn ring(&self, center: Cell, ring: i32) -> Vec<usize> {
+		let mut indices = Vec::new();
+
+		for x in center.0 - ring..=center.0 + ring {
+			for y in center.1 - ring..=center.1 + ring {
+				if (x - center.0).abs() != ring && (y - center.1).abs() != ring {
+					continue;
+				}
+
+				if let Some(cell) = self.cells.get(&(x, y)) {
+					indices.extend_from_slice(cell);
+				}
+			}
+		}
+
+		indices
+	}
+}
+
+/// Static colliders of random size spread over `-extent..extent` on both axes; the fixture of
+/// the broadphase tests and benchmarks
+pub fn scatter(count: usize, extent: f32, random: &mut Random) -> Vec<BoundInfo> {
+	(0..count)
+		.map(|i| BoundInfo {
+			id: i as i32,
+			bounds: FRect::new(
+				random.range(-extent, extent),
+				random.range(-extent, extent),
+				random.range(1.0, 300.0),
+				random.range(1.0, 100.0),
+			),
+			mask: 0,
+			body: BodyType::Static,
+			collision: CollisionType::Solid,
+			collider: true,
+		})
+		.collect()
+}
+
+fn distance(rect: FRect, point: FPoint) -> f32 {
+	let dx = f32::max(f32::max(rect.left() - point.x, point.x - rect.right()), 0.0);
+	let dy = f32::max(f32::max(rect.top() - point.y, point.y - rect.bottom()), 0.0);
+
+	(dx * dx + dy * dy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::{FPoint, FRect};
+	use crate::game_object::broadphase::{distance, scatter, Broadphase};
+	use crate::game_object::{BodyType, BoundInfo, CollisionType, PhysicsVector};
+	use crate::math::bounds::Bounds;
+	use crate::util::Random;
+
+	fn ids(objects: Vec<&BoundInfo>) -> Vec<i32> {
+		objects.into_iter().map(|o| o.id).collect()
+	}
+
+	#[test]
+	fn test_queries_match_brute_force() {
+		let mut random = Random::new(3);
+		let objects = scatter(500, 2000.0, &mut random);
+		let broadphase = Broadphase::new(128.0, objects.clone());
+
+		for _ in 0..100 {
+			let rect = FRect::new(
+				random.range(-2500.0, 2500.0),
+				random.range(-2500.0, 2500.0),
+				random.range(0.0, 400.0),
+				random.range(0.0, 400.0),
+			);
+			let point = FPoint::new(random.range(-2500.0, 2500.0), random.range(-2500.0, 2500.0));
+
+			let expected: Vec<i32> = objects.iter().filter(|o| o.bounds.intersects(rect)).map(|o| o.id).collect();
+			assert_eq!(ids(broadphase.query_rect(rect)), expected);
+
+			let expected: Vec<i32> = objects.iter().filter(|o| o.bounds.contains(point)).map(|o| o.id).collect();
+			assert_eq!(ids(broadphase.query_point(point)), expected);
+
+			let expected = objects
+				.iter()
+				.filter(|o| o.id % 2 == 0)
+				.min_by(|a, b| distance(a.bounds, point).total_cmp(&distance(b.bounds, point)))
+				.map(|o| o.id);
+			assert_eq!(broadphase.nearest(point, |o| o.id % 2 == 0).map(|o| o.id), expected);
+		}
+
+		// far outside of the grid
+		for point in [FPoint::new(1.0e7, -1.0e7), FPoint::new(-3.0e6, 100.0)] {
+			let expected = objects
+				.iter()
+				.min_by(|a, b| distance(a.bounds, point).total_cmp(&distance(b.bounds, point)))
+				.map(|o| o.id);
+			assert_eq!(broadphase.nearest(point, |_| true).map(|o| o.id), expected);
+		}
+
+		assert!(Broadphase::new(128.0, Vec::new()).nearest(FPoint::new(0.0, 0.0), |_| true).is_none());
+	}
+
+	fn level() -> Broadphase {
+		let object = |id, bounds, mask| BoundInfo {
+			id,
+			bounds,
+			mask,
+			body: BodyType::Static,
+			collision: CollisionType::Solid,
+			collider: true,
+		};
+
+		Broadphase::new(
+			128.0,
+			vec![
+				object(1, FRect::new(0.0, 500.0, 800.0, 20.0), 0),
+				object(2, FRect::new(300.0, 400.0, 50.0, 50.0), 0b01),
+				object(3, FRect::new(600.0, 0.0, 20.0, 600.0), 0b10),
+			],
+		)
+	}
+
+	#[test]
+	fn test_raycast() {
+		let level = level();
+		let down = PhysicsVector { x: 0.0, y: 1.0 };
+		let right = PhysicsVector { x: 1.0, y: 0.0 };
+
+		let hit = level.raycast(FPoint::new(100.0, 100.0), down, 1000.0, 0, None).unwrap();
+		assert_eq!(hit.id, 1);
+		assert_eq!(hit.point, FPoint::new(100.0, 500.0));
+		assert_eq!(hit.normal, PhysicsVector { x: 0.0, y: -1.0 });
+		assert_eq!(hit.distance, 400.0);
+
+		// the closer box is in front of the ground
+		assert_eq!(level.raycast(FPoint::new(320.0, 100.0), down, 1000.0, 0, None).unwrap().id, 2);
+		assert!(level.raycast(FPoint::new(100.0, 100.0), down, 300.0, 0, None).is_none());
+
+		let hit = level.raycast(FPoint::new(0.0, 425.0), right, 1000.0, 0b10, None).unwrap();
+		assert_eq!(hit.id, 3);
+		assert!(hit.point.x == 600.0 && hit.point.y == 425.0);
+		assert_eq!(level.raycast(FPoint::new(0.0, 425.0), right, 1000.0, 0b01, None).unwrap().id, 2);
+		assert_eq!(level.raycast(FPoint::new(0.0, 425.0), right, 1000.0, 0b10, Some(3)), None);
+
+		// the origin is inside the ground
+		assert!(level.raycast(FPoint::new(100.0, 510.0), down, 1000.0, 0, None).is_none());
+		assert!(level.raycast(FPoint::new(100.0, 100.0), PhysicsVector::default(), 1000.0, 0, None).is_none());
+	}
+
+	#[test]
+	fn test_boxcast() {
+		let level = level();
+		let bounds = FRect::new(250.0, 300.0, 64.0, 64.0);
+		let down = PhysicsVector { x: 0.0, y: 1.0 };
+
+		// a ray from the center misses the box, the whole width doesn't
+		assert_eq!(level.raycast(bounds.center(), down, 1000.0, 0, None).unwrap().id, 1);
+
+		let hit = level.boxcast(bounds, down, 1000.0, 0, None).unwrap();
+		assert_eq!(hit.id, 2);
+		assert_eq!(hit.distance, 36.0);
+		assert_eq!(hit.point, FPoint::new(282.0, 368.0));
+		assert_eq!(hit.normal, PhysicsVector { x: 0.0, y: -1.0 });
+	}
+
+	#[test]
+	fn test_update_matches_a_new_grid() {
+		let mut random = Random::new(5);
+		let mut objects = scatter(300, 1500.0, &mut random);
+		let mut broadphase = Broadphase::new(128.0, objects.clone());
+
+		for _ in 0..10 {
+			for object in objects.iter_mut().filter(|o| o.id % 3 == 0) {
+				object.bounds.x += random.range(-200.0, 200.0);
+				object.bounds.y += random.range(-200.0, 200.0);
+			}
+
+			broadphase.update(objects.clone());
+			let rebuilt = Broadphase::new(128.0, objects.clone());
+
+			for _ in 0..50 {
+				let rect = FRect::new(
+					random.range(-2000.0, 2000.0),
+					random.range(-2000.0, 2000.0),
+					random.range(0.0, 400.0),
+					random.range(0.0, 400.0),
+				);
+				let point = FPoint::new(random.range(-2000.0, 2000.0), random.range(-2000.0, 2000.0));
+
+				assert_eq!(ids(broadphase.query_rect(rect)), ids(rebuilt.query_rect(rect)));
+				assert_eq!(
+					broadphase.nearest(point, |_| true).map(|o| o.id),
+					rebuilt.nearest(point, |_| true).map(|o| o.id)
+				);
+			}
+		}
+
+		// a removed object is gone from the grid
+		objects.remove(0);
+		broadphase.update(objects.clone());
+
+		assert!(broadphase.query_rect(FRect::new(-2000.0, -2000.0, 4000.0, 4000.0)).iter().all(|o| o.id != 0));
+		assert_eq!(broadphase.objects.len(), 299);
+	}
+
+	/// The timings are in `cargo bench`; this only checks that the grid does the work that
+	/// makes it faster than brute force
+	#[test]
+	fn test_candidates_grow_with_the_density() {
+		let mut candidates_per_object = Vec::new();
+
+		for count in [1_000, 2_000, 4_000, 8_000] {
+			let mut random = Random::new(1);
+			// keeps the density constant, like a level that grows with its objects
+			let extent = (count as f32).sqrt() * 50.0;
+			let objects = scatter(count, extent, &mut random);
+			let broadphase = Broadphase::new(128.0, objects.clone());
+
+			let mut candidates = 0;
+
+			for a in &objects {
+				let (min, max) = broadphase.cell_range(a.bounds);
+				candidates += broadphase.candidates(min, max).len();
+
+				let brute_force = objects.iter().filter(|b| b.bounds.intersects(a.bounds)).count();
+				assert_eq!(broadphase.query_rect(a.bounds).len(), brute_force);
+			}
+
+			candidates_per_object.push(candidates as f32 / count as f32);
+		}
+
+		// brute force checks every object against all of them, the grid against its neighbours
+		// only, which doesn't depend on the number of objects
+		let first = candidates_per_object[0];
+
+		assert!(first < 50.0, "{candidates_per_object:?}");
+		assert!(candidates_per_object.iter().all(|&c| c < first * 1.5), "{candidates_per_object:?}");
+	}
+}
@@ -0,0 +1,391 @@
+use sdl3::render::FRect;
+use serde::{Deserialize, Serialize};
+use crate::game_object::PhysicsVector;
+use crate::math::bounds::Bounds;
+use crate::serialization::AssetSize;
+use crate::util::Random;
+
+/// Effects behaviours can ask the camera for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraCommand {
+	/// shakes the view by up to `strength` pixels, fading out over `duration` seconds
+	Shake {
+		strength: f32,
+		duration: f32,
+	},
+	Zoom(f32),
+	/// moves and zooms so that the region fills the view instead of following the target
+	Fit(FRect),
+	/// follows the target again with the zoom from before `Fit`
+	Follow,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Shake {
+	strength: f32,
+	duration: f32,
+	elapsed: f32,
+}
+
+impl Shake {
+	fn current_strength(&self) -> f32 {
+		self.strength * (1.0 - self.elapsed / self.duration)
+	}
+}
+
+/// The part of the level that gets rendered; follows a target object and stays inside the level
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct Camera {
+	/// id of the object to follow; the player if not set
+	#[serde(default = "_default_target")]
+	pub target: Option<i32>,
+	/// size of the area around the center the target can move in without the camera following
+	#[serde(default)]
+	pub dead_zone: AssetSize,
+	/// how fast the camera catches up with the target; higher is faster, 0.0 follows immediately
+	#[serde(default)]
+	pub smoothing: f32,
+	#[serde(default = "_default_zoom")]
+	pub zoom: f32,
+	/// size of the window the camera renders to
+	#[serde(skip)]
+	pub viewport: AssetSize,
+	#[serde(skip)]
+	center: PhysicsVector,
+	/// center and zoom at the start of the last tick, used to interpolate while rendering
+	#[serde(skip)]
+	previous_center: PhysicsVector,
+	#[serde(skip)]
+	previous_zoom: f32,
+	/// zoom the camera is moving towards
+	#[serde(skip)]
+	desired_zoom: Option<f32>,
+	#[serde(skip)]
+	fit: Option<FRect>,
+	/// zoom to go back to once the camera follows the target again
+	#[serde(skip)]
+	follow_zoom: Option<f32>,
+	#[serde(skip)]
+	shake: Option<Shake>,
+	#[serde(skip)]
+	shake_offset: PhysicsVector,
+}
+
+fn _default_target() -> Option<i32> {
+	Some(-1)
+}
+
+fn _default_zoom() -> f32 {
+	1.0
+}
+
+impl Default for Camera {
+	fn default() -> Self {
+		Self {
+			target: _default_target(),
+			dead_zone: AssetSize::default(),
+			smoothing: 0.0,
+			zoom: _default_zoom(),
+			viewport: AssetSize::default(),
+			center: PhysicsVector::default(),
+			previous_center: PhysicsVector::default(),
+			previous_zoom: _default_zoom(),
+			desired_zoom: None,
+			fit: None,
+			follow_zoom: None,
+			shake: None,
+			shake_offset: PhysicsVector::default(),
+		}
+	}
+}
+
+impl Camera {
+	pub fn new(viewport: AssetSize) -> Self {
+		Self {
+			viewport,
+			..Self::default()
+		}
+	}
+
+	/// The area of the world that is visible; `alpha` is the fraction of a tick that has passed
+	/// since the last one
+	pub fn view(&self, alpha: f32) -> FRect {
+		let center = self.previous_center + (self.center - self.previous_center) * alpha + self.shake_offset;
+		let size = self.view_size(self.zoom_at(alpha));
+
+		FRect {
+			x: center.x - size.x / 2.0,
+			y: center.y - size.y / 2.0,
+			w: size.x,
+			h: size.y,
+		}
+	}
+
+	fn zoom_at(&self, alpha: f32) -> f32 {
+		self.previous_zoom + (self.zoom - self.previous_zoom) * alpha
+	}
+
+	fn view_size(&self, zoom: f32) -> PhysicsVector {
+		PhysicsVector {
+			x: self.viewport.w / zoom,
+			y: self.viewport.h / zoom,
+		}
+	}
+
+	pub fn apply(&mut self, command: CameraCommand) {
+		match command {
+			CameraCommand::Shake { strength, duration } => {
+				// a weaker shake doesn't cut a stronger one short
+				let stronger = self.shake.is_none_or(|shake| strength >= shake.current_strength());
+
+				if stronger && duration > 0.0 {
+					self.shake = Some(Shake { strength, duration, elapsed: 0.0 });
+				}
+			}
+			CameraCommand::Zoom(zoom) => self.desired_zoom = Some(zoom),
+			CameraCommand::Fit(region) => {
+				if self.fit.is_none() {
+					self.follow_zoom = Some(self.desired_zoom.unwrap_or(self.zoom));
+				}
+
+				self.fit = Some(region);
+				self.desired_zoom = Some(f32::min(self.viewport.w / region.w, self.viewport.h / region.h));
+			}
+			CameraCommand::Follow => {
+				self.fit = None;
+
+				if let Some(zoom) = self.follow_zoom.take() {
+					self.desired_zoom = Some(zoom);
+				}
+			}
+		}
+	}
+
+	/// Moves the camera onto the target without smoothing
+	pub fn snap(&mut self, target: Option<FRect>, level_bounds: FRect) {
+		if let Some(target) = target {
+			self.center = PhysicsVector::from(target.center());
+		}
+
+		if let Some(zoom) = self.desired_zoom.take() {
+			self.zoom = zoom;
+		}
+
+		self.center = self.clamp(self.center, level_bounds);
+		self.previous_center = self.center;
+		self.previous_zoom = self.zoom;
+	}
+
+	/// Moves towards the target or the region to fit; the shake is random, so it draws from the
+	/// random generator of the world
+	pub fn update(&mut self, target: Option<FRect>, level_bounds: FRect, delta_t: f64, random: &mut Random) {
+		self.previous_center = self.center;
+		self.previous_zoom = self.zoom;
+
+		let t = if self.smoothing > 0.0 {
+			1.0 - (-self.smoothing * delta_t as f32).exp()
+		} else {
+			1.0
+		};
+
+		if let Some(zoom) = self.desired_zoom {
+			self.zoom += (zoom - self.zoom) * t;
+		}
+
+		let desired = match (self.fit, target) {
+			(Some(region), _) => Some(PhysicsVector::from(region.center())),
+			(None, Some(target)) => {
+				let target = PhysicsVector::from(target.center());
+
+				Some(PhysicsVector {
+					x: follow(self.center.x, target.x, self.dead_zone.w / 2.0),
+					y: follow(self.center.y, target.y, self.dead_zone.h / 2.0),
+				})
+			}
+			(None, None) => None,
+		};
+
+		if let Some(desired) = desired {
+			self.center += (desired - self.center) * t;
+		}
+
+		self.center = self.clamp(self.center, level_bounds);
+
+		self.shake_offset = PhysicsVector::default();
+
+		if let Some(shake) = &mut self.shake {
+			shake.elapsed += delta_t as f32;
+
+			if shake.elapsed >= shake.duration {
+				self.shake = None;
+			} else {
+				let strength = shake.current_strength();
+
+				self.shake_offset = PhysicsVector {
+					x: random.range(-strength, strength),
+					y: random.range(-strength, strength),
+				};
+			}
+		}
+	}
+
+	/// Keeps the view inside the level; levels smaller than the view are centered
+	fn clamp(&self, center: PhysicsVector, level_bounds: FRect) -> PhysicsVector {
+		let half = self.view_size(self.zoom) / 2.0;
+		let level_center = level_bounds.center();
+
+		let clamp_axis = |value: f32, min: f32, max: f32, middle: f32| {
+			if min > max { middle } else { value.clamp(min, max) }
+		};
+
+		PhysicsVector {
+			x: clamp_axis(center.x, level_bounds.left() + half.x, level_bounds.right() - half.x, level_center.x),
+			y: clamp_axis(center.y, level_bounds.top() + half.y, level_bounds.bottom() - half.y, level_center.y),
+		}
+	}
+
+	/// Transforms a rect in world space into window space
+	pub fn project(&self, rect: FRect, alpha: f32) -> FRect {
+		let view = self.view(alpha);
+		let zoom = self.zoom_at(alpha);
+
+		FRect {
+			x: (rect.x - view.x) * zoom,
+			y: (rect.y - view.y) * zoom,
+			w: rect.w * zoom,
+			h: rect.h * zoom,
+		}
+	}
+}
+
+/// The position on one axis the camera has to move to so that the target is inside the dead zone
+fn follow(center: f32, target: f32, half_dead_zone: f32) -> f32 {
+	if target > center + half_dead_zone {
+		target - half_dead_zone
+	} else if target < center - half_dead_zone {
+		target + half_dead_zone
+	} else {
+		center
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::game_object::camera::{Camera, CameraCommand};
+	use crate::serialization::AssetSize;
+	use crate::util::Random;
+
+	static LEVEL: FRect = FRect { x: 0.0, y: 0.0, w: 2000.0, h: 600.0 };
+
+	fn camera() -> Camera {
+		Camera {
+			viewport: AssetSize { w: 800.0, h: 600.0 },
+			dead_zone: AssetSize { w: 100.0, h: 100.0 },
+			..Camera::default()
+		}
+	}
+
+	fn player(x: f32) -> Option<FRect> {
+		Some(FRect { x, y: 300.0, w: 64.0, h: 64.0 })
+	}
+
+	#[test]
+	fn test_follow_with_dead_zone() {
+		let mut camera = camera();
+
+		camera.snap(player(968.0), LEVEL);
+		assert_eq!(camera.view(1.0).x, 600.0);
+
+		// inside the dead zone
+		camera.update(player(1000.0), LEVEL, 0.1, &mut Random::new(1));
+		assert_eq!(camera.view(1.0).x, 600.0);
+
+		// the target leaves the dead zone by 50 pixels
+		camera.update(player(1068.0), LEVEL, 0.1, &mut Random::new(1));
+		assert_eq!(camera.view(1.0).x, 650.0);
+		assert_eq!(camera.view(0.5).x, 625.0);
+	}
+
+	#[test]
+	fn test_smoothing() {
+		let mut camera = Camera { smoothing: 5.0, ..camera() };
+		let mut random = Random::new(1);
+
+		camera.snap(player(968.0), LEVEL);
+		camera.update(player(1168.0), LEVEL, 0.1, &mut random);
+
+		let x = camera.view(1.0).x;
+
+		assert!(x > 600.0 && x < 750.0);
+
+		for _ in 0..100 {
+			camera.update(player(1168.0), LEVEL, 0.1, &mut random);
+		}
+
+		assert!((camera.view(1.0).x - 750.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn test_clamp_and_transform() {
+		let mut camera = camera();
+
+		camera.snap(player(0.0), LEVEL);
+		assert_eq!(camera.view(1.0), FRect { x: 0.0, y: 0.0, w: 800.0, h: 600.0 });
+
+		camera.snap(player(1990.0), LEVEL);
+		assert_eq!(camera.view(1.0).x, 1200.0);
+		assert_eq!(camera.project(FRect { x: 1300.0, y: 100.0, w: 10.0, h: 10.0 }, 1.0), FRect { x: 100.0, y: 100.0, w: 10.0, h: 10.0 });
+
+		// zoomed out further than the level is high
+		camera.zoom = 0.5;
+		camera.snap(player(968.0), LEVEL);
+		assert_eq!(camera.view(1.0), FRect { x: 200.0, y: -300.0, w: 1600.0, h: 1200.0 });
+		assert_eq!(camera.project(FRect { x: 200.0, y: 0.0, w: 10.0, h: 10.0 }, 1.0), FRect { x: 0.0, y: 150.0, w: 5.0, h: 5.0 });
+	}
+
+	#[test]
+	fn test_shake_fades_out() {
+		let mut camera = camera();
+		let mut random = Random::new(1);
+
+		camera.snap(player(968.0), LEVEL);
+		camera.apply(CameraCommand::Shake { strength: 10.0, duration: 0.5 });
+		camera.update(player(968.0), LEVEL, 0.1, &mut random);
+
+		let offset = camera.view(1.0).x - 600.0;
+
+		assert!(offset != 0.0 && offset.abs() <= 8.0);
+
+		// a weaker shake doesn't replace the running one
+		camera.apply(CameraCommand::Shake { strength: 1.0, duration: 5.0 });
+
+		for _ in 0..5 {
+			camera.update(player(968.0), LEVEL, 0.1, &mut random);
+		}
+
+		assert_eq!(camera.view(1.0).x, 600.0);
+	}
+
+	#[test]
+	fn test_fit_and_follow() {
+		let mut camera = camera();
+		let mut random = Random::new(1);
+
+		camera.snap(player(968.0), LEVEL);
+		camera.apply(CameraCommand::Fit(FRect { x: 1000.0, y: 150.0, w: 400.0, h: 300.0 }));
+		camera.update(player(968.0), LEVEL, 0.1, &mut random);
+
+		assert_eq!(camera.view(1.0), FRect { x: 1000.0, y: 150.0, w: 400.0, h: 300.0 });
+		// the zoom is interpolated as well, halfway from 1 to 2
+		let view = camera.view(0.5);
+
+		assert!((view.w - 800.0 / 1.5).abs() < 0.001);
+		assert!((view.h - 600.0 / 1.5).abs() < 0.001);
+
+		camera.apply(CameraCommand::Follow);
+		camera.update(player(968.0), LEVEL, 0.1, &mut random);
+
+		assert_eq!(camera.view(1.0), FRect { x: 650.0, y: 0.0, w: 800.0, h: 600.0 });
+	}
+}
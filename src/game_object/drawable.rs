@@ -1,7 +1,10 @@
 use std::cmp::Ordering;
 use sdl3::pixels::Color;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sdl3::render::FRect;
 use serde::ser::SerializeMap;
+use crate::game_object::PhysicsVector;
+use crate::math::bounds::Bounds;
 use crate::serialization::{AssetColor, AssetId};
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,6 +30,76 @@ impl Ord for DrawLayer {
 	}
 }
 
+/// What the bounds of a drawable are relative to
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordinateSpace {
+	/// moves with the camera
+	#[default]
+	World,
+	/// window coordinates, e.g. for a hud or a sky that stays in place
+	Screen,
+}
+
+/// How a drawable scrolls when the camera moves, e.g. for distant background layers
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Parallax {
+	/// 1.0 moves with the world, 0.0 stays in place and values in between scroll slower
+	pub factor: PhysicsVector,
+	/// tiles the drawable over the whole width of the view
+	#[serde(default)]
+	pub repeat_x: bool,
+	/// tiles the drawable over the whole height of the view
+	#[serde(default)]
+	pub repeat_y: bool,
+}
+
+impl Parallax {
+	/// The rects in world space the drawable covers when seen through `view`
+	pub fn rects(&self, bounds: FRect, view: FRect) -> Vec<FRect> {
+		let x = bounds.x + view.x * (1.0 - self.factor.x);
+		let y = bounds.y + view.y * (1.0 - self.factor.y);
+
+		let xs = tiles(x, bounds.w, view.left(), view.right(), self.repeat_x);
+		let ys = tiles(y, bounds.h, view.top(), view.bottom(), self.repeat_y);
+
+		let mut rects = Vec::with_capacity(xs.len() * ys.len());
+
+		for y in &ys {
+			for x in &xs {
+				rects.push(FRect { x: *x, y: *y, w: bounds.w, h: bounds.h });
+			}
+		}
+
+		rects
+	}
+}
+
+/// The starts of the tiles on one axis that cover `min..max`, or just `start` if the axis doesn't
+/// repeat
+fn tiles(start: f32, size: f32, min: f32, max: f32, repeat: bool) -> Vec<f32> {
+	if !repeat || size <= 0.0 {
+		return vec![start];
+	}
+
+	let mut position = start + ((min - start) / size).floor() * size;
+	let mut tiles = Vec::new();
+
+	while position < max {
+		tiles.push(position);
+		position += size;
+	}
+
+	tiles
+}
+
+/// A frame of a sprite sheet; drawn instead of the texture
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sprite {
+	pub sheet: AssetId,
+	#[serde(default)]
+	pub frame: usize,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 pub struct Drawable {
 	pub z: DrawLayer,
@@ -35,6 +108,15 @@ pub struct Drawable {
 	pub texture_id: Option<AssetId>, // index of the texture
 	#[serde(default)]
 	pub tint_texture: bool,       // if the texture should be tinted by the color
+	#[serde(default)]
+	pub space: CoordinateSpace,
+	#[serde(default)]
+	pub parallax: Option<Parallax>,
+	#[serde(default)]
+	pub sprite: Option<Sprite>,
+	/// shown instead of the texture, set by the behaviours in every tick
+	#[serde(skip)]
+	pub texture_override: Option<AssetId>,
 }
 
 impl Default for Drawable {
@@ -44,6 +126,10 @@ impl Default for Drawable {
 			color: None,
 			texture_id: None,
 			tint_texture: bool::default(),
+			space: CoordinateSpace::default(),
+			parallax: None,
+			sprite: None,
+			texture_override: None,
 		}
 	}
 }
@@ -68,3 +154,41 @@ where
 		s.serialize_none()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::game_object::drawable::Parallax;
+	use crate::game_object::PhysicsVector;
+
+	static VIEW: FRect = FRect { x: 1000.0, y: 0.0, w: 800.0, h: 600.0 };
+
+	#[test]
+	fn test_parallax_scrolls_slower() {
+		let parallax = Parallax {
+			factor: PhysicsVector { x: 0.5, y: 1.0 },
+			repeat_x: false,
+			repeat_y: false,
+		};
+
+		let rects = parallax.rects(FRect { x: 0.0, y: 100.0, w: 1200.0, h: 200.0 }, VIEW);
+
+		assert_eq!(rects, vec![FRect { x: 500.0, y: 100.0, w: 1200.0, h: 200.0 }]);
+	}
+
+	#[test]
+	fn test_parallax_repeats() {
+		let parallax = Parallax {
+			factor: PhysicsVector { x: 0.25, y: 0.0 },
+			repeat_x: true,
+			repeat_y: false,
+		};
+
+		let rects = parallax.rects(FRect { x: 0.0, y: 0.0, w: 300.0, h: 600.0 }, VIEW);
+		let xs: Vec<f32> = rects.iter().map(|r| r.x).collect();
+
+		// the tiles start at 750.0 and are shifted to the left edge of the view
+		assert_eq!(xs, vec![750.0, 1050.0, 1350.0, 1650.0]);
+		assert!(rects.iter().all(|r| r.y == 0.0));
+	}
+}
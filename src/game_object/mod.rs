@@ -1,32 +1,106 @@
 pub mod behaviour;
+pub mod broadphase;
+pub mod camera;
+pub mod tilemap;
 pub mod world;
 pub mod drawable;
 
 extern crate sdl3;
 
-use std::collections::HashSet;
 use crate::serialization::AssetBounds;
-use crate::game_object::behaviour::{BehaviourParameter, BehaviourType};
+use crate::game_object::behaviour::{BehaviourParameter, BehaviourType, WorldCommand};
+use crate::game_object::behaviour::collision::CollisionBehaviour;
+use crate::game_object::broadphase::Broadphase;
 use crate::math::bounds::Bounds;
 use crate::math::vector2::Vector2;
 use sdl3::render::FRect;
 use serde::{Deserialize, Serialize};
-use crate::actions::Action;
+use crate::input::InputState;
 use crate::game_object::drawable::{DrawLayer, Drawable};
 
 pub type PhysicsVector = Vector2<f32>;
 
 pub type ObjectMask = u32;
-pub type BoundInfo = (i32, FRect, ObjectMask);
-pub type CollisionInfo = BoundInfo;
+
+/// How an object takes part in collision resolution
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyType {
+	/// never moves
+	#[default]
+	Static,
+	/// moves on its own but is never pushed by others
+	Kinematic,
+	/// gets pushed out of everything it overlaps
+	Dynamic,
+}
+
+/// How other bodies collide with an object
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollisionType {
+	/// blocks from all sides
+	#[default]
+	Solid,
+	/// only blocks bodies landing on top of it, they can jump up through it
+	OneWay,
+	/// reports contacts but never blocks
+	Trigger,
+	/// doesn't block, controllable objects can climb it like a ladder
+	Climbable,
+}
+
+impl CollisionType {
+	/// Whether objects of this type stop others
+	pub fn blocks(&self) -> bool {
+		matches!(self, CollisionType::Solid | CollisionType::OneWay)
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundInfo {
+	pub id: i32,
+	pub bounds: FRect,
+	pub mask: ObjectMask,
+	pub body: BodyType,
+	pub collision: CollisionType,
+	/// whether the object has a collision behaviour
+	pub collider: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionInfo {
+	pub id: i32,
+	/// the area both objects overlap in
+	pub bounds: FRect,
+	pub body: BodyType,
+	pub collision: CollisionType,
+	/// points away from the other object, along the axis with the smaller overlap
+	pub normal: PhysicsVector,
+}
+
+impl CollisionInfo {
+	/// Whether the other object stops movement; triggers and ladders don't
+	pub fn is_solid(&self) -> bool {
+		self.collision.blocks()
+	}
+}
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GameObject {
+	/// templates get their id when they are spawned
+	#[serde(default)]
     pub id: i32,
 	#[serde(with = "AssetBounds")]
     pub bounds: FRect,
+	/// bounds at the start of the last simulation step, used to interpolate while rendering
+	#[serde(skip)]
+	pub previous_bounds: Option<FRect>,
 	#[serde(default)]
 	pub mask: ObjectMask,
+	/// inferred from the behaviours if not set
+	#[serde(default)]
+	pub body: Option<BodyType>,
+	#[serde(default)]
+	pub collision: CollisionType,
     pub drawable: Option<Drawable>,
     pub behaviours: Vec<BehaviourType>,
 }
@@ -41,18 +115,34 @@ impl GameObject {
                 w: f32::default(),
                 h: f32::default(),
             },
+			previous_bounds: None,
 			mask: ObjectMask::default(),
+			body: None,
+			collision: CollisionType::default(),
             drawable: Some(Drawable::default()),
             behaviours: Vec::new(),
         }
     }
 
-    pub fn tick(&mut self, delta_t: f64, world_bounds: FRect, actions: &HashSet<Action>, other_bounds: &Vec<BoundInfo>) {
+    pub fn tick(
+		&mut self,
+		delta_t: f64,
+		world_bounds: FRect,
+		input: &InputState,
+		broadphase: &Broadphase,
+	) -> Vec<WorldCommand> {
+        let collider = self.collider().copied();
         let behaviours = &mut self.behaviours;
         let mut bounds = self.bounds;
+		self.previous_bounds = Some(bounds);
+
 		let mut collisions = Vec::new();
 		let mut force = None;
 		let mut impulse = None;
+		let mut motion = None;
+		let mut speed_control = None;
+		let mut texture = None;
+		let mut commands = Vec::new();
 
         for i in 0..behaviours.len() {
             let behaviour = &mut behaviours[i];
@@ -61,12 +151,15 @@ impl GameObject {
                 BehaviourParameter {
                     id: self.id,
                     bounds,
-                    actions,
+                    input,
 					world_bounds,
-                    other_bounds,
+                    broadphase,
+					collider,
 					collisions: &collisions,
 					force,
 					impulse,
+					motion,
+					speed_control,
                 },
                 delta_t,
             );
@@ -79,10 +172,84 @@ impl GameObject {
 				collisions = c
 			}
 
-			force = result.force;
-			impulse = result.impulse;
+			if let Some(m) = result.motion {
+				motion = Some(m)
+			}
+
+			if let Some(control) = result.speed_control {
+				speed_control = Some(control)
+			}
+
+			if let Some(t) = result.texture {
+				texture = Some(t)
+			}
+
+			if let Some(frame) = result.frame
+				&& let Some(sprite) = self.drawable.as_mut().and_then(|d| d.sprite.as_mut())
+			{
+				sprite.frame = frame;
+			}
+
+			// carried along, so that behaviours in between don't hide them from the physics
+			if let Some(f) = result.force {
+				force = Some(f)
+			}
+
+			if let Some(i) = result.impulse {
+				impulse = Some(i)
+			}
+
+			commands.extend(result.commands);
         }
 
         self.bounds = bounds;
+
+		if let Some(drawable) = self.drawable.as_mut() {
+			drawable.texture_override = texture;
+		}
+
+        commands
     }
+
+	pub fn body_type(&self) -> BodyType {
+		if let Some(body) = self.body {
+			return body;
+		}
+
+		if self.behaviours.iter().any(|b| matches!(b, BehaviourType::Physics(_))) {
+			BodyType::Dynamic
+		} else if self.behaviours.iter().any(|b| matches!(b, BehaviourType::Dvd(_) | BehaviourType::Platform(_))) {
+			BodyType::Kinematic
+		} else {
+			BodyType::Static
+		}
+	}
+
+	/// The collision behaviour; objects without one are not resolved against others
+	pub fn collider(&self) -> Option<&CollisionBehaviour> {
+		self.behaviours.iter().find_map(|b| match b {
+			BehaviourType::Collision(collision) => Some(collision),
+			_ => None,
+		})
+	}
+
+	pub fn bound_info(&self) -> BoundInfo {
+		BoundInfo {
+			id: self.id,
+			bounds: self.bounds,
+			mask: self.mask,
+			body: self.body_type(),
+			collision: self.collision,
+			collider: self.collider().is_some(),
+		}
+	}
+
+	/// The bounds between the previous and the current simulation step; `alpha` is the fraction
+	/// of a step that has passed since the last one
+	pub fn interpolated_bounds(&self, alpha: f32) -> FRect {
+		match self.previous_bounds {
+			Some(previous) => previous.lerp(self.bounds, alpha),
+			None => self.bounds,
+		}
+	}
 }
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+use sdl3::render::FRect;
+use serde::{Deserialize, Serialize};
+use crate::game_object::drawable::{DrawLayer, Drawable, Sprite};
+use crate::game_object::{CollisionType, ObjectMask, PhysicsVector};
+use crate::serialization::{AssetId, AssetSize};
+
+fn _default_z() -> DrawLayer {
+	DrawLayer::Background(0)
+}
+
+/// A grid of tiles drawn from the frames of a sprite sheet
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Tilemap {
+	/// the sprite sheet the tiles are cut from
+	pub tileset: AssetId,
+	/// size of a tile in the level
+	pub tile_size: AssetSize,
+	/// top left corner of the first tile
+	#[serde(default)]
+	pub position: PhysicsVector,
+	#[serde(default = "_default_z")]
+	pub z: DrawLayer,
+	/// rows of tiles; 0 is empty, everything else is a frame of the tileset counted from 1
+	pub tiles: Vec<Vec<usize>>,
+	/// whether the tiles block other objects
+	#[serde(default)]
+	pub solid: bool,
+	#[serde(default)]
+	pub mask: ObjectMask,
+	/// how the colliders of a solid tilemap block other objects, e.g. one-way for platforms
+	#[serde(default)]
+	pub collision: CollisionType,
+	/// one drawable for every frame of the tileset, shared by all tiles that show the frame
+	#[serde(skip)]
+	drawables: Vec<Drawable>,
+}
+
+impl Tilemap {
+	pub fn init(&mut self) {
+		let frames = self.tiles.iter().flatten().max().copied().unwrap_or(0);
+
+		self.drawables = (0..frames)
+			.map(|frame| Drawable {
+				z: self.z,
+				sprite: Some(Sprite { sheet: self.tileset, frame }),
+				..Drawable::default()
+			})
+			.collect();
+	}
+
+	fn tile_rect(&self, column: usize, row: usize, columns: usize, rows: usize) -> FRect {
+		FRect {
+			x: self.position.x + column as f32 * self.tile_size.w,
+			y: self.position.y + row as f32 * self.tile_size.h,
+			w: columns as f32 * self.tile_size.w,
+			h: rows as f32 * self.tile_size.h,
+		}
+	}
+
+	/// The range of indices on one axis that `min..max` covers
+	fn visible_range(start: f32, size: f32, min: f32, max: f32, count: usize) -> (usize, usize) {
+		if size <= 0.0 {
+			return (0, 0);
+		}
+
+		let first = ((min - start) / size).floor().clamp(0.0, count as f32) as usize;
+		let last = ((max - start) / size).ceil().clamp(0.0, count as f32) as usize;
+
+		(first, last)
+	}
+
+	/// The tiles that `view` shows, row by row; only the visible part of the grid is visited
+	pub fn visible_tiles(&self, view: FRect) -> Vec<(FRect, &Drawable)> {
+		let columns = self.tiles.iter().map(|row| row.len()).max().unwrap_or(0);
+		let (first_row, last_row) = Self::visible_range(self.position.y, self.tile_size.h, view.y, view.y + view.h, self.tiles.len());
+		let (first_column, last_column) = Self::visible_range(self.position.x, self.tile_size.w, view.x, view.x + view.w, columns);
+
+		let mut tiles = Vec::new();
+
+		for (row, cells) in self.tiles.iter().enumerate().skip(first_row).take(last_row.saturating_sub(first_row)) {
+			let visible = cells.iter().enumerate().skip(first_column).take(last_column.saturating_sub(first_column));
+
+			for (column, tile) in visible {
+				if let Some(drawable) = tile.checked_sub(1).and_then(|frame| self.drawables.get(frame)) {
+					tiles.push((self.tile_rect(column, row, 1, 1), drawable));
+				}
+			}
+		}
+
+		tiles
+	}
+
+	/// Rects covering every tile of a solid tilemap; neighbouring tiles are merged into as few rects
+	/// as possible so that bodies don't catch on the seams between them
+	pub fn colliders(&self) -> Vec<FRect> {
+		if !self.solid {
+			return Vec::new();
+		}
+
+		// (first column, end column) of a horizontal run -> first row of the rect it belongs to
+		let mut open: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+		let mut rects = Vec::new();
+
+		for (row, cells) in self.tiles.iter().enumerate() {
+			let mut next = BTreeMap::new();
+
+			for run in runs(cells) {
+				let top = open.remove(&run).unwrap_or(row);
+
+				next.insert(run, top);
+			}
+
+			for ((start, end), top) in open {
+				rects.push(self.tile_rect(start, top, end - start, row - top));
+			}
+
+			open = next;
+		}
+
+		for ((start, end), top) in open {
+			rects.push(self.tile_rect(start, top, end - start, self.tiles.len() - top));
+		}
+
+		rects.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+		rects
+	}
+}
+
+/// The start and end columns of the runs of tiles in a row
+fn runs(cells: &[usize]) -> Vec<(usize, usize)> {
+	let mut runs = Vec::new();
+	let mut start = None;
+
+	for (column, tile) in cells.iter().enumerate() {
+		match (start, *tile != 0) {
+			(None, true) => start = Some(column),
+			(Some(first), false) => {
+				runs.push((first, column));
+				start = None;
+			}
+			_ => {}
+		}
+	}
+
+	if let Some(first) = start {
+		runs.push((first, cells.len()));
+	}
+
+	runs
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::game_object::tilemap::Tilemap;
+
+	fn tilemap() -> Tilemap {
+		let mut tilemap: Tilemap = serde_json::from_str(r#"{
+			"tileset": 1,
+			"tile_size": { "w": 32, "h": 32 },
+			"position": { "x": 100, "y": 0 },
+			"solid": true,
+			"tiles": [
+				[0, 0, 0, 3],
+				[1, 2, 2, 0],
+				[1, 2, 2, 0],
+				[0, 1, 0, 0]
+			]
+		}"#).unwrap();
+
+		tilemap.init();
+		tilemap
+	}
+
+	#[test]
+	fn test_colliders_are_merged() {
+		let colliders = tilemap().colliders();
+
+		assert_eq!(colliders, vec![
+			FRect { x: 196.0, y: 0.0, w: 32.0, h: 32.0 },
+			FRect { x: 100.0, y: 32.0, w: 96.0, h: 64.0 },
+			FRect { x: 132.0, y: 96.0, w: 32.0, h: 32.0 },
+		]);
+	}
+
+	#[test]
+	fn test_visible_tiles() {
+		let tilemap = tilemap();
+		let tiles = tilemap.visible_tiles(FRect { x: 0.0, y: 40.0, w: 140.0, h: 40.0 });
+		let frames: Vec<usize> = tiles.iter().map(|(_, d)| d.sprite.unwrap().frame).collect();
+
+		assert_eq!(frames, vec![0, 1, 0, 1]);
+		assert_eq!(tiles[1].0, FRect { x: 132.0, y: 32.0, w: 32.0, h: 32.0 });
+	}
+}
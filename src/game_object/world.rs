@@ -1,13 +1,23 @@
-use std::collections::HashSet;
 use crate::game_object::behaviour::collision::CollisionBehaviour;
 use crate::game_object::behaviour::controllable::ControllableBehaviour;
 use crate::game_object::behaviour::physics::PhysicsBehaviour;
-use crate::game_object::behaviour::{BehaviourType};
-use crate::game_object::{BoundInfo, Bounds, DrawLayer, Drawable, GameObject, PhysicsVector};
+use std::collections::HashMap;
+use crate::game_object::behaviour::{BehaviourType, WorldCommand};
+use crate::game_object::behaviour::collision::contact;
+use crate::game_object::broadphase::{Broadphase, CastHit};
+use crate::game_object::camera::Camera;
+use crate::game_object::tilemap::Tilemap;
+use crate::game_object::drawable::{CoordinateSpace, Sprite};
+use crate::game_object::{BodyType, BoundInfo, Bounds, CollisionType, DrawLayer, Drawable, GameObject, ObjectMask, PhysicsVector};
+use crate::serialization::AssetSize;
 use crate::serialization::level::LevelData;
 use sdl3::pixels::Color;
-use sdl3::render::FRect;
-use crate::actions::Action;
+use sdl3::render::{FPoint, FRect};
+use crate::input::InputState;
+use crate::util::Random;
+
+/// Edge length of the cells of the broadphase grid; about the size of a typical object
+static CELL_SIZE: f32 = 128.0;
 
 #[derive(PartialEq, Eq, Debug, Default)]
 pub enum BorderType {
@@ -29,10 +39,19 @@ pub struct World {
     scale: f32,
     borders: Borders,
     game_objects: Vec<GameObject>,
+    /// the objects as they are after the last tick
+    broadphase: Broadphase,
+    camera: Camera,
+    tilemaps: Vec<Tilemap>,
+    templates: HashMap<String, GameObject>,
+    /// the id the next spawned object gets
+    next_id: i32,
+    seed: u64,
+    random: Random,
 }
 
 impl World {
-    pub fn new(w: f32, h: f32) -> Self {
+    pub fn new(w: f32, h: f32, seed: u64) -> Self {
         Self {
             bounds: FRect {
                 x: f32::default(),
@@ -43,12 +62,42 @@ impl World {
             scale: 1.0,
             borders: Borders::default(),
             game_objects: Vec::new(),
+            broadphase: Broadphase::default(),
+            camera: Camera::new(AssetSize { w, h }),
+            tilemaps: Vec::new(),
+            templates: HashMap::new(),
+            next_id: 1,
+            seed,
+            random: Random::new(seed),
         }
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Every load starts from the seed again, so a level always plays out the same for the same input
     pub fn load_level(&mut self, level_data: &LevelData) {
+		self.random = Random::new(self.seed);
+
 		let game_objects = &level_data.objects;
 		self.game_objects = game_objects.clone();
+		self.templates = level_data.templates.clone();
+		self.next_id = game_objects.iter().map(|o| o.id).max().unwrap_or(0).max(0) + 1;
+
+		self.tilemaps = level_data.tilemaps.clone();
+
+		for tilemap in &mut self.tilemaps {
+			tilemap.init();
+		}
+
+		self.add_tile_colliders();
+
+		for object in &mut self.game_objects {
+			for behaviour in &mut object.behaviours {
+				behaviour.init(&mut self.random);
+			}
+		}
 
 		self.bounds = level_data.bounds;
 
@@ -65,63 +114,473 @@ impl World {
             color: Some(Color::MAGENTA),
             texture_id: Some(player_data.texture_id),
             tint_texture: true,
+            space: CoordinateSpace::World,
+            parallax: None,
+            sprite: player_data.sprite_sheet.map(|sheet| Sprite { sheet, frame: 0 }),
+            texture_override: None,
         });
+        let controllable = player_data
+            .controllable
+            .unwrap_or_else(|| ControllableBehaviour::new(5.0, 15.0));
+
+        // a player that crouches on duck only drops through one-way platforms with down
         player
             .behaviours
-            .push(BehaviourType::Collision(CollisionBehaviour::new()));
+            .push(BehaviourType::Collision(CollisionBehaviour {
+                drop_through: true,
+                drop_on_duck: !controllable.can_duck,
+                ..CollisionBehaviour::new()
+            }));
         player
             .behaviours
-            .push(BehaviourType::Controllable(ControllableBehaviour::new(
-                5.0,
-                15.0,
-            )));
+            .push(BehaviourType::Controllable(controllable));
+
         player.behaviours.push(BehaviourType::Physics(PhysicsBehaviour::new(
             self.bounds,
             PhysicsVector::default(),
             2.0,
         )));
 
+        if let Some(animation) = &player_data.animation {
+            player
+                .behaviours
+                .push(BehaviourType::Animation(animation.clone()));
+        }
+
         self.add_game_object(player);
+
+        self.broadphase = self.build_broadphase();
+
+        let viewport = self.camera.viewport;
+
+        self.camera = level_data.camera;
+        self.camera.viewport = viewport;
+        self.camera.snap(self.camera_target(), self.bounds);
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Size of the window the world is rendered to
+    pub fn set_viewport(&mut self, w: f32, h: f32) {
+        self.camera.viewport = AssetSize { w, h };
+        self.camera.snap(None, self.bounds);
+    }
+
+    fn camera_target(&self) -> Option<FRect> {
+        let target = self.camera.target?;
+
+        self.game_objects.iter().find(|o| o.id == target).map(|o| o.bounds)
+    }
+
+    /// Adds a static object without a drawable for every collider of the solid tilemaps
+    fn add_tile_colliders(&mut self) {
+        let colliders: Vec<(FRect, ObjectMask, CollisionType)> = self
+            .tilemaps
+            .iter()
+            .flat_map(|tilemap| {
+                tilemap
+                    .colliders()
+                    .into_iter()
+                    .map(|rect| (rect, tilemap.mask, tilemap.collision))
+            })
+            .collect();
+
+        for (bounds, mask, collision) in colliders {
+            let mut object = GameObject::new(self.next_id);
+
+            self.next_id += 1;
+
+            object.bounds = bounds;
+            object.mask = mask;
+            object.body = Some(BodyType::Static);
+            object.collision = collision;
+            object.drawable = None;
+            object
+                .behaviours
+                .push(BehaviourType::Collision(CollisionBehaviour {
+                    mask,
+                    ..CollisionBehaviour::new()
+                }));
+
+            self.game_objects.push(object);
+        }
     }
 
-    fn add_game_object(&mut self, object: GameObject) {
+    /// Resolves the random parts of the behaviours, once per object
+    fn add_game_object(&mut self, mut object: GameObject) {
+        for behaviour in &mut object.behaviours {
+            behaviour.init(&mut self.random);
+        }
+
         self.game_objects.push(object);
         self.game_objects
-            .sort_by_key(|b| b.drawable.as_ref().map(|d| d.z).unwrap());
+            .sort_by_key(|b| b.drawable.as_ref().map(|d| d.z));
     }
 
-    pub fn tick(&mut self, delta_t: f64, actions: &HashSet<Action>) {
-        let rects: Vec<BoundInfo> = self.game_objects.iter().map(|o| (o.id, o.bounds, o.mask)).collect();
+    /// Adds a copy of a template with a new id; returns the id
+    pub fn spawn(&mut self, template: &str, position: FPoint) -> Option<i32> {
+        let Some(template) = self.templates.get(template) else {
+            eprintln!("there is no template '{template}'");
+            return None;
+        };
+
+        let mut object = template.clone();
+        let id = self.next_id;
+
+        self.next_id += 1;
+
+        object.id = id;
+        object.bounds.set_center(position);
+
+        self.add_game_object(object);
+
+        Some(id)
+    }
+
+    pub fn despawn(&mut self, id: i32) {
+        self.game_objects.retain(|o| o.id != id);
+    }
+
+    fn apply(&mut self, commands: Vec<WorldCommand>) {
+        for command in commands {
+            match command {
+                WorldCommand::Spawn { template, position } => {
+                    self.spawn(&template, position);
+                }
+                WorldCommand::Despawn(id) => self.despawn(id),
+                WorldCommand::Camera(command) => self.camera.apply(command),
+            }
+        }
+    }
+
+    pub fn tick(&mut self, delta_t: f64, input: &InputState) {
+        let mut commands = Vec::new();
 
         for i in 0..self.game_objects.len() {
             let game_object = &mut self.game_objects[i];
 
-            game_object.tick(delta_t, self.bounds, actions, &rects);
+            commands.extend(game_object.tick(delta_t, self.bounds, input, &self.broadphase));
         }
+
+        self.apply(commands);
+        self.carry_riders();
+
+        self.update_broadphase();
+        self.resolve_collisions(input);
+        self.update_broadphase();
+
+        self.camera.update(self.camera_target(), self.bounds, delta_t, &mut self.random);
     }
 
-    pub fn get_drawables(&self) -> Vec<(FRect, &Drawable)> {
+    /// Moves dynamic bodies that stood on a kinematic object at the start of the tick by as much as
+    /// the object has moved during the tick, so that they don't slide off moving platforms
+    fn carry_riders(&mut self) {
+        let platforms: Vec<(BoundInfo, FRect)> = self
+            .game_objects
+            .iter()
+            .filter(|o| o.body_type() == BodyType::Kinematic && o.collider().is_some() && o.collision.blocks())
+            .filter_map(|o| o.previous_bounds.map(|previous| (o.bound_info(), previous)))
+            .filter(|(platform, previous)| platform.bounds.x != previous.x || platform.bounds.y != previous.y)
+            .collect();
+
+        if platforms.is_empty() {
+            return;
+        }
+
+        for game_object in &mut self.game_objects {
+            let Some(collider) = game_object.collider() else {
+                continue;
+            };
+
+            if game_object.body_type() != BodyType::Dynamic {
+                continue;
+            }
+
+            let Some(start) = game_object.previous_bounds else {
+                continue;
+            };
+
+            let ground = platforms.iter().find(|(platform, previous)| {
+                collider.collides_with(platform.mask)
+                    && contact(start, *previous).is_some_and(|(normal, _)| normal.y < 0.0)
+            });
+
+            if let Some((platform, previous)) = ground {
+                game_object.bounds.x += platform.bounds.x - previous.x;
+                game_object.bounds.y += platform.bounds.y - previous.y;
+            }
+        }
+    }
+
+    /// Pushes dynamic bodies out of everything they overlap along the axis of least penetration;
+    /// two dynamic bodies each move half of the way. Triggers and ladders never push.
+    fn resolve_collisions(&mut self, input: &InputState) {
+        for i in 0..self.game_objects.len() {
+            let game_object = &self.game_objects[i];
+
+            let Some(collider) = game_object.collider() else {
+                continue;
+            };
+
+            if game_object.body_type() != BodyType::Dynamic {
+                continue;
+            }
+
+            let mut bounds = game_object.bounds;
+
+            let mut others: Vec<&BoundInfo> = self
+                .broadphase
+                .query_rect(bounds)
+                .into_iter()
+                .filter(|other| {
+                    other.id != game_object.id
+                        && other.collider
+                        && other.collision.blocks()
+                        && collider.collides_with(other.mask)
+                })
+                .collect();
+
+            // resolving the largest overlap first keeps bodies from catching on the seams between
+            // neighbouring objects
+            others.sort_by(|a, b| {
+                let area = |r: &BoundInfo| {
+                    let overlap = r.bounds.intersection(bounds);
+                    f32::max(overlap.w, 0.0) * f32::max(overlap.h, 0.0)
+                };
+
+                area(b).total_cmp(&area(a))
+            });
+
+            for other in others {
+                let Some((normal, penetration)) = collider.contact(other.collision, bounds, other.bounds, input) else {
+                    continue;
+                };
+
+                if penetration <= 0.0 {
+                    continue;
+                }
+
+                let share = if other.body == BodyType::Dynamic { 0.5 } else { 1.0 };
+                let push = normal * (penetration * share);
+
+                bounds.x += push.x;
+                bounds.y += push.y;
+            }
+
+            self.game_objects[i].bounds = bounds;
+        }
+    }
+
+    fn build_broadphase(&self) -> Broadphase {
+        Broadphase::new(CELL_SIZE, self.game_objects.iter().map(|o| o.bound_info()).collect())
+    }
+
+    /// Only the objects that moved change their cells; spawning or despawning builds it again
+    fn update_broadphase(&mut self) {
+        self.broadphase.update(self.game_objects.iter().map(|o| o.bound_info()).collect());
+    }
+
+    /// See [`Broadphase::raycast`]
+    pub fn raycast(
+        &self,
+        origin: FPoint,
+        direction: PhysicsVector,
+        max_distance: f32,
+        mask: ObjectMask,
+        ignore: Option<i32>,
+    ) -> Option<CastHit> {
+        self.broadphase.raycast(origin, direction, max_distance, mask, ignore)
+    }
+
+    /// See [`Broadphase::boxcast`]
+    pub fn boxcast(
+        &self,
+        bounds: FRect,
+        direction: PhysicsVector,
+        max_distance: f32,
+        mask: ObjectMask,
+        ignore: Option<i32>,
+    ) -> Option<CastHit> {
+        self.broadphase.boxcast(bounds, direction, max_distance, mask, ignore)
+    }
+
+    pub fn get_game_objects(&self) -> &Vec<GameObject> {
+        &self.game_objects
+    }
+
+    /// The interpolated bounds of all drawables the camera can see; screen space drawables are
+    /// always visible, parallax drawables show up once for every repetition and tilemaps once for
+    /// every visible tile
+    pub fn get_drawables(&self, alpha: f32) -> Vec<(FRect, &Drawable)> {
         let mut vec = Vec::new();
+        let view = self.camera.view(alpha);
 
         for i in 0..self.game_objects.len() {
             let game_object = &self.game_objects[i];
-            let id = game_object.id;
 
             if let Some(drawable) = game_object.drawable.as_ref() {
-                if !self.is_visible(game_object.bounds) {
-                    let a = format!("not in view {id}");
-                    dbg!(a);
+                let bounds = game_object.interpolated_bounds(alpha);
+
+                if drawable.space == CoordinateSpace::Screen {
+                    vec.push((bounds, drawable));
                     continue;
                 }
 
-                vec.push((game_object.bounds, drawable));
+                let rects = match drawable.parallax {
+                    Some(parallax) => parallax.rects(bounds, view),
+                    None => vec![bounds],
+                };
+
+                for rect in rects {
+                    if rect.intersects(view) {
+                        vec.push((rect, drawable));
+                    }
+                }
             }
         }
 
+        for tilemap in &self.tilemaps {
+            vec.extend(tilemap.visible_tiles(view));
+        }
+
+        // stable, so the tiles of a layer stay together and the renderer draws them in one batch
+        vec.sort_by_key(|(_, drawable)| drawable.z);
+
         vec
     }
+}
 
-    fn is_visible(&self, bounds: FRect) -> bool {
-        bounds.intersects(self.bounds)
-    }
+#[cfg(test)]
+mod tests {
+	use sdl3::render::{FPoint, FRect};
+	use crate::actions::Action;
+	use crate::game_object::behaviour::BehaviourType;
+	use crate::game_object::world::World;
+	use crate::game_object::PhysicsVector;
+	use crate::headless::HeadlessRunner;
+	use crate::math::bounds::Bounds;
+	use crate::serialization::script::ActionScript;
+	use crate::test_level::{input, player_bounds, script, TestLevel};
+	use crate::util::Random;
+
+	#[test]
+	fn test_runs_are_identical() {
+		let script = script([(30, input([Action::Jump, Action::MoveLeft]))]);
+
+		let mut first = TestLevel::new().runner();
+		let mut second = TestLevel::new().runner();
+
+		first.run(90, &script);
+		second.run(90, &script);
+
+		assert_eq!(first.to_json().unwrap(), second.to_json().unwrap());
+	}
+
+	#[test]
+	fn test_player_rests_on_ground() {
+		let mut runner = TestLevel::new().runner();
+
+		runner.run(120, &ActionScript::default());
+
+		let bounds = player_bounds(&runner);
+
+		// pushed out of the ground instead of sinking into it
+		assert!((bounds.bottom() - 580.0).abs() < 0.01);
+
+		runner.run(60, &ActionScript::default());
+
+		assert!((player_bounds(&runner).bottom() - 580.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn test_spawn_and_despawn() {
+		// drops fall from the spawner and vanish once they are below y = 300
+		let mut runner = TestLevel::new()
+			.object(r#"{
+				"id": 7,
+				"bounds": { "x": 600, "y": 0, "w": 10, "h": 10 },
+				"drawable": { "z": { "Background": 1 } },
+				"behaviours": [{ "type": "Spawner", "template": "drop", "interval": 0.5, "limit": 3 }]
+			}"#)
+			.template("drop", r#"{
+				"bounds": { "x": 0, "y": 0, "w": 4, "h": 4 },
+				"drawable": { "z": { "Foreground": 2 } },
+				"behaviours": [
+					{ "type": "Physics", "speed": { "x": 0, "y": 0 }, "mass": 1 },
+					{ "type": "Despawn", "bounds": { "x": 0, "y": 0, "w": 800, "h": 300 } }
+				]
+			}"#)
+			.runner();
+
+		let drops = |runner: &HeadlessRunner| -> Vec<i32> {
+			runner.game_objects().iter().map(|o| o.id).filter(|id| *id > 7).collect()
+		};
+
+		runner.run(31, &ActionScript::default());
+		assert_eq!(drops(&runner), vec![8]);
+
+		runner.run(30, &ActionScript::default());
+		assert_eq!(drops(&runner), vec![8, 9]);
+
+		// the limit is reached and all drops have fallen out of their area
+		runner.run(240, &ActionScript::default());
+		assert!(drops(&runner).is_empty());
+		assert_eq!(runner.game_objects().len(), 3);
+	}
+	#[test]
+	fn test_spawn_draws_random_speed_once() {
+		let level = TestLevel::new().template("ball", r#"{
+			"bounds": { "x": 0, "y": 0, "w": 8, "h": 8 },
+			"behaviours": [{
+				"type": "Dvd",
+				"speed": { "type": "Random", "min": { "x": 20, "y": 20 }, "max": { "x": 200, "y": 200 } }
+			}]
+		}"#);
+
+		let mut world = World::new(800.0, 600.0, 5);
+		world.load_level(level.level());
+
+		let id = world.spawn("ball", FPoint::new(400.0, 300.0)).unwrap();
+
+		// nothing in the level draws from the generator, so the speed is its first draw
+		let mut random = Random::new(5);
+		let expected = PhysicsVector { x: random.range(20.0, 200.0), y: random.range(20.0, 200.0) };
+
+		let ball = world.get_game_objects().iter().find(|o| o.id == id).unwrap();
+		let Some(BehaviourType::Dvd(dvd)) = ball.behaviours.first() else {
+			panic!("the ball has no dvd behaviour");
+		};
+
+		assert_eq!(dvd.velocity, expected);
+	}
+	#[test]
+	fn test_casts_filter_by_mask() {
+		let level = TestLevel::new().object(r#"{
+			"id": 2,
+			"bounds": { "x": 300, "y": 400, "w": 50, "h": 50 },
+			"mask": 1,
+			"behaviours": [{ "type": "Collision" }]
+		}"#);
+
+		let mut world = World::new(800.0, 600.0, 0);
+		world.load_level(level.level());
+
+		let down = PhysicsVector { x: 0.0, y: 1.0 };
+
+		let hit = world.raycast(FPoint::new(320.0, 100.0), down, 1000.0, 0b01, None).unwrap();
+		assert_eq!(hit.id, 2);
+		assert_eq!(hit.point, FPoint::new(320.0, 400.0));
+		assert_eq!(hit.distance, 300.0);
+
+		// the box has another mask, the ground none at all
+		assert_eq!(world.raycast(FPoint::new(320.0, 100.0), down, 1000.0, 0b10, None).unwrap().id, 1);
+		assert_eq!(world.raycast(FPoint::new(320.0, 100.0), down, 1000.0, 0b01, Some(2)).unwrap().id, 1);
+
+		let hit = world.boxcast(FRect::new(250.0, 300.0, 64.0, 64.0), down, 1000.0, 0b01, None).unwrap();
+		assert_eq!(hit.id, 2);
+		assert_eq!(hit.distance, 36.0);
+		assert_eq!(hit.normal, PhysicsVector { x: 0.0, y: -1.0 });
+	}
 }
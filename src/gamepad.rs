@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use sdl3::event::Event;
+use sdl3::GamepadSubsystem;
+use sdl3::gamepad::{Axis, Button, Gamepad};
+use crate::actions::Action;
+use crate::input::{InputAxis, InputSource, InputState};
+
+static AXIS_MAX: f32 = i16::MAX as f32;
+
+/// Connected gamepads
+pub struct Gamepads {
+	subsystem: Option<GamepadSubsystem>,
+	connected: HashMap<u32, Gamepad>,
+	/// the raw left stick of each gamepad that moved it
+	sticks: HashMap<u32, (f32, f32)>,
+}
+
+impl Gamepads {
+	/// Without a subsystem no gamepads get opened, but events are still handled
+	pub fn new(subsystem: Option<GamepadSubsystem>) -> Self {
+		Self {
+			subsystem,
+			connected: HashMap::new(),
+			sticks: HashMap::new(),
+		}
+	}
+
+	/// Opens and closes gamepads as they get plugged in, maps their buttons to actions and the
+	/// left stick to the move axes
+	pub fn handle_event(
+		&mut self,
+		event: &Event,
+		buttonmap: &HashMap<Button, Action>,
+		input: &mut InputState,
+		dead_zone: f32,
+	) {
+		match event {
+			Event::ControllerDeviceAdded { which, .. } => {
+				if let Some(subsystem) = &self.subsystem {
+					match subsystem.open(*which) {
+						Ok(gamepad) => {
+							self.connected.insert(*which, gamepad);
+						}
+						Err(err) => eprintln!("could not open gamepad {which}: {err}"),
+					}
+				}
+			}
+			Event::ControllerDeviceRemoved { which, .. } => {
+				self.connected.remove(which);
+				self.sticks.remove(which);
+
+				input.release_all(InputSource::Gamepad(*which));
+				self.update_axes(input, dead_zone);
+			}
+			Event::ControllerButtonDown { which, button, .. } => {
+				if let Some(action) = buttonmap.get(button) {
+					input.press(InputSource::Gamepad(*which), *action);
+				}
+			}
+			Event::ControllerButtonUp { which, button, .. } => {
+				if let Some(action) = buttonmap.get(button) {
+					input.release(InputSource::Gamepad(*which), *action);
+				}
+			}
+			Event::ControllerAxisMotion { which, axis, value, .. } => {
+				let stick = self.sticks.entry(*which).or_default();
+				let value = *value as f32 / AXIS_MAX;
+
+				match axis {
+					Axis::LeftX => stick.0 = value,
+					Axis::LeftY => stick.1 = value,
+					_ => return,
+				}
+
+				self.update_axes(input, dead_zone);
+			}
+			_ => {}
+		}
+	}
+
+	/// The stick pushed furthest moves the player, so a resting gamepad doesn't cancel out
+	/// the one in use
+	fn update_axes(&self, input: &mut InputState, dead_zone: f32) {
+		let furthest = |value: fn(&(f32, f32)) -> f32| {
+			self.sticks
+				.values()
+				.map(value)
+				.max_by(|a, b| a.abs().total_cmp(&b.abs()))
+				.unwrap_or(0.0)
+		};
+
+		input.set_axis(InputAxis::MoveX, furthest(|stick| stick.0), dead_zone);
+		input.set_axis(InputAxis::MoveY, furthest(|stick| stick.1), dead_zone);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::{HashMap, HashSet};
+	use sdl3::event::Event;
+	use sdl3::gamepad::{Axis, Button};
+	use crate::actions::Action;
+	use crate::gamepad::Gamepads;
+	use crate::input::{InputAxis, InputSource, InputState};
+
+	#[test]
+	fn test_buttons() {
+		let mut gamepads = Gamepads::new(None);
+		let buttonmap = HashMap::from([(Button::South, Action::Jump)]);
+		let mut input = InputState::default();
+
+		let down = Event::ControllerButtonDown { timestamp: 0, which: 1, button: Button::South };
+		let other = Event::ControllerButtonDown { timestamp: 0, which: 1, button: Button::North };
+		let up = Event::ControllerButtonUp { timestamp: 0, which: 1, button: Button::South };
+
+		gamepads.handle_event(&down, &buttonmap, &mut input, 0.0);
+		gamepads.handle_event(&other, &buttonmap, &mut input, 0.0);
+
+		assert_eq!(input.actions, HashSet::from([Action::Jump]));
+
+		gamepads.handle_event(&up, &buttonmap, &mut input, 0.0);
+
+		assert!(input.actions.is_empty());
+	}
+
+	#[test]
+	fn test_buttons_of_several_sources() {
+		let mut gamepads = Gamepads::new(None);
+		let buttonmap = HashMap::from([(Button::South, Action::Jump)]);
+		let mut input = InputState::default();
+
+		let button = |which, down| match down {
+			true => Event::ControllerButtonDown { timestamp: 0, which, button: Button::South },
+			false => Event::ControllerButtonUp { timestamp: 0, which, button: Button::South },
+		};
+
+		// the key is still held when the button goes up
+		input.press(InputSource::Keyboard, Action::Jump);
+		gamepads.handle_event(&button(1, true), &buttonmap, &mut input, 0.0);
+		gamepads.handle_event(&button(1, false), &buttonmap, &mut input, 0.0);
+
+		assert!(input.pressed(Action::Jump));
+
+		// and the button when the key goes up
+		gamepads.handle_event(&button(1, true), &buttonmap, &mut input, 0.0);
+		input.release(InputSource::Keyboard, Action::Jump);
+
+		assert!(input.pressed(Action::Jump));
+
+		gamepads.handle_event(&button(2, true), &buttonmap, &mut input, 0.0);
+		gamepads.handle_event(
+			&Event::ControllerDeviceRemoved { timestamp: 0, which: 1 },
+			&buttonmap,
+			&mut input,
+			0.0,
+		);
+
+		assert!(input.pressed(Action::Jump));
+
+		gamepads.handle_event(&button(2, false), &buttonmap, &mut input, 0.0);
+
+		assert!(input.actions.is_empty());
+	}
+
+	#[test]
+	fn test_stick() {
+		let mut gamepads = Gamepads::new(None);
+		let buttonmap = HashMap::new();
+		let mut input = InputState::default();
+
+		let motion = |axis, value| Event::ControllerAxisMotion { timestamp: 0, which: 1, axis, value };
+
+		gamepads.handle_event(&motion(Axis::LeftX, i16::MIN), &buttonmap, &mut input, 0.2);
+		assert_eq!(input.axis(InputAxis::MoveX), -1.0);
+
+		gamepads.handle_event(&motion(Axis::LeftX, 1_000), &buttonmap, &mut input, 0.2);
+		assert_eq!(input.axis(InputAxis::MoveX), 0.0);
+
+		gamepads.handle_event(&motion(Axis::LeftY, i16::MAX / 2), &buttonmap, &mut input, 0.0);
+		assert!((input.axis(InputAxis::MoveY) - 0.5).abs() < 0.001);
+
+		gamepads.handle_event(&motion(Axis::RightX, i16::MAX), &buttonmap, &mut input, 0.0);
+		assert_eq!(input.axis(InputAxis::AimX), 0.0);
+
+		gamepads.handle_event(
+			&Event::ControllerDeviceRemoved { timestamp: 0, which: 1 },
+			&buttonmap,
+			&mut input,
+			0.0,
+		);
+		assert_eq!(input.axis(InputAxis::MoveY), 0.0);
+	}
+
+	#[test]
+	fn test_sticks_of_several_gamepads() {
+		let mut gamepads = Gamepads::new(None);
+		let buttonmap = HashMap::new();
+		let mut input = InputState::default();
+
+		let motion = |which, value| Event::ControllerAxisMotion { timestamp: 0, which, axis: Axis::LeftX, value };
+
+		gamepads.handle_event(&motion(1, i16::MAX), &buttonmap, &mut input, 0.0);
+		// a second gamepad resting in its center doesn't stop the first one
+		gamepads.handle_event(&motion(2, 0), &buttonmap, &mut input, 0.0);
+
+		assert_eq!(input.axis(InputAxis::MoveX), 1.0);
+
+		gamepads.handle_event(&motion(2, i16::MIN), &buttonmap, &mut input, 0.0);
+
+		assert_eq!(input.axis(InputAxis::MoveX), -1.0);
+
+		gamepads.handle_event(
+			&Event::ControllerDeviceRemoved { timestamp: 0, which: 2 },
+			&buttonmap,
+			&mut input,
+			0.0,
+		);
+
+		assert_eq!(input.axis(InputAxis::MoveX), 1.0);
+	}
+}
@@ -1,9 +1,14 @@
+use std::collections::HashMap;
 use sdl3::render::FRect;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use crate::errors::LayoutError;
 use crate::gui::UiElement;
 use crate::serialization::{AssetBounds, AssetId};
 
-#[derive(Deserialize, Debug, Clone, Copy)]
+/// Allowed difference between two constraints that define the same anchor
+static EPSILON: f32 = 0.001;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Anchor {
 	Top,
 	Bottom,
@@ -15,7 +20,26 @@ pub enum Anchor {
 	CenterHorizontally,
 }
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+impl Anchor {
+	fn is_horizontal(&self) -> bool {
+		matches!(self, Anchor::Leading | Anchor::Trailing | Anchor::Width | Anchor::CenterHorizontally)
+	}
+
+	fn value(&self, rect: FRect) -> f32 {
+		match self {
+			Anchor::Top => rect.y,
+			Anchor::Bottom => rect.y + rect.h,
+			Anchor::Leading => rect.x,
+			Anchor::Trailing => rect.x + rect.w,
+			Anchor::Width => rect.w,
+			Anchor::Height => rect.h,
+			Anchor::CenterVertically => rect.y + rect.h / 2.0,
+			Anchor::CenterHorizontally => rect.x + rect.w / 2.0,
+		}
+	}
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Relation {
 	Equal,
 	Greater,
@@ -25,49 +49,419 @@ pub enum Relation {
 	None,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+fn _default_factor() -> f32 {
+	1.0
+}
+
+/// `own_anchor <relation> other_anchor * factor + constant`
+///
+/// The other element is a sibling if `other_id` is set, otherwise the parent. With
+/// `Relation::None` the anchor is set to `constant` directly.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 pub struct Constraint {
 	pub own_anchor: Anchor,
+	#[serde(default)]
 	pub other_anchor: Option<Anchor>,
+	#[serde(default)]
 	pub other_id: Option<AssetId>,
 	pub relation: Relation,
+	#[serde(default = "_default_factor")]
 	pub factor: f32,
+	#[serde(default)]
 	pub constant: f32,
 }
 
 impl Constraint {
-	pub fn to_asset_bounds(&self, root_view: &UiElement) -> Result<AssetBounds, String> {
-		todo!();
+	/// The value the constraint compares `own_anchor` against; `other` is relative to the parent
+	fn target(&self, other: FRect) -> f32 {
+		if self.relation == Relation::None {
+			return self.constant;
+		}
+
+		let anchor = self.other_anchor.unwrap_or(self.own_anchor);
+
+		anchor.value(other) * self.factor + self.constant
+	}
+
+	fn is_satisfied(&self, own: FRect, other: FRect) -> bool {
+		let value = self.own_anchor.value(own);
+		let target = self.target(other);
+
+		match self.relation {
+			Relation::Equal | Relation::None => (value - target).abs() <= EPSILON,
+			Relation::Greater => value > target,
+			Relation::GreaterEqual => value >= target - EPSILON,
+			Relation::Less => value < target,
+			Relation::LessEqual => value <= target + EPSILON,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConstraintSet {
+	pub constraints: Vec<Constraint>,
+}
+
+impl ConstraintSet {
+	/// Computes the bounds relative to the parent from the already resolved `siblings`
+	///
+	/// Each axis needs exactly two distinct anchors defined by `Equal` or `None` constraints,
+	/// the remaining constraints have to hold for the result.
+	fn resolve(
+		&self,
+		id: AssetId,
+		parent: FRect,
+		siblings: &HashMap<AssetId, FRect>,
+	) -> Result<FRect, LayoutError> {
+		let mut horizontal: Vec<(Anchor, f32)> = Vec::new();
+		let mut vertical: Vec<(Anchor, f32)> = Vec::new();
+
+		for constraint in &self.constraints {
+			if !matches!(constraint.relation, Relation::Equal | Relation::None) {
+				continue;
+			}
+
+			let other = other_rect(constraint, id, parent, siblings)?;
+			let value = constraint.target(other);
+			let anchor = constraint.own_anchor;
+			let axis = if anchor.is_horizontal() { &mut horizontal } else { &mut vertical };
+
+			match axis.iter().find(|(a, _)| *a == anchor) {
+				Some((_, v)) if (v - value).abs() > EPSILON => {
+					return Err(LayoutError::Conflicting { id });
+				}
+				Some(_) => {}
+				None => axis.push((anchor, value)),
+			}
+		}
 
-		if self.relation != Relation::None {
-			if self.other_anchor.is_none() || self.other_id.is_none() {
-				return Err(String::from("missing anchor"))
+		let (x, w) = solve_axis(id, &horizontal)?;
+		let (y, h) = solve_axis(id, &vertical)?;
+
+		let bounds = FRect { x, y, w, h };
+
+		for constraint in &self.constraints {
+			let other = other_rect(constraint, id, parent, siblings)?;
+
+			if !constraint.is_satisfied(bounds, other) {
+				return Err(LayoutError::Unsatisfiable { id });
 			}
 		}
 
-		let mut x = 0.0;
-		let mut y = 0.0;
-		let mut w = 0.0;
-		let mut h = 0.0;
+		Ok(bounds)
+	}
 
-		Ok(AssetBounds {
-			x,
-			y,
-			w,
-			h,
-		})
+	fn dependencies(&self) -> impl Iterator<Item = AssetId> + '_ {
+		self.constraints
+			.iter()
+			.filter(|c| c.relation != Relation::None)
+			.filter_map(|c| c.other_id)
 	}
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
-pub struct ConstraintSet {
-	pub constraint: [Constraint; 4]
+/// Position and size on one axis from two anchor values; the anchors are named after the
+/// horizontal axis but the vertical ones work the same way
+fn solve_axis(id: AssetId, anchors: &[(Anchor, f32)]) -> Result<(f32, f32), LayoutError> {
+	if anchors.len() < 2 {
+		return Err(LayoutError::Underdetermined { id });
+	}
+
+	if anchors.len() > 2 {
+		return Err(LayoutError::Conflicting { id });
+	}
+
+	let normalize = |anchor: Anchor| match anchor {
+		Anchor::Top => Anchor::Leading,
+		Anchor::Bottom => Anchor::Trailing,
+		Anchor::Height => Anchor::Width,
+		Anchor::CenterVertically => Anchor::CenterHorizontally,
+		anchor => anchor,
+	};
+
+	let (a, a_value) = anchors[0];
+	let (b, b_value) = anchors[1];
+
+	let (start, size) = match ((normalize(a), a_value), (normalize(b), b_value)) {
+		((Anchor::Leading, l), (Anchor::Trailing, t)) | ((Anchor::Trailing, t), (Anchor::Leading, l)) => {
+			(l, t - l)
+		}
+		((Anchor::Leading, l), (Anchor::Width, w)) | ((Anchor::Width, w), (Anchor::Leading, l)) => (l, w),
+		((Anchor::Leading, l), (Anchor::CenterHorizontally, c))
+		| ((Anchor::CenterHorizontally, c), (Anchor::Leading, l)) => (l, (c - l) * 2.0),
+		((Anchor::Trailing, t), (Anchor::Width, w)) | ((Anchor::Width, w), (Anchor::Trailing, t)) => {
+			(t - w, w)
+		}
+		((Anchor::Trailing, t), (Anchor::CenterHorizontally, c))
+		| ((Anchor::CenterHorizontally, c), (Anchor::Trailing, t)) => (c - (t - c), (t - c) * 2.0),
+		((Anchor::Width, w), (Anchor::CenterHorizontally, c))
+		| ((Anchor::CenterHorizontally, c), (Anchor::Width, w)) => (c - w / 2.0, w),
+		_ => return Err(LayoutError::Underdetermined { id }),
+	};
+
+	if size < 0.0 {
+		return Err(LayoutError::Unsatisfiable { id });
+	}
+
+	Ok((start, size))
 }
 
-#[derive(Deserialize, Debug, Clone, Copy)]
+/// The rect a constraint refers to, in the coordinate space of the parent
+fn other_rect(
+	constraint: &Constraint,
+	id: AssetId,
+	parent: FRect,
+	siblings: &HashMap<AssetId, FRect>,
+) -> Result<FRect, LayoutError> {
+	let parent_rect = FRect {
+		x: 0.0,
+		y: 0.0,
+		w: parent.w,
+		h: parent.h,
+	};
+
+	match constraint.other_id {
+		// the anchor is set to the constant, the other element is never read and may not exist
+		_ if constraint.relation == Relation::None => Ok(parent_rect),
+		None => Ok(parent_rect),
+		Some(other_id) => siblings
+			.get(&other_id)
+			.copied()
+			.ok_or(LayoutError::MissingElement { id, other_id }),
+	}
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum Frame {
 	#[serde(with = "AssetBounds")]
 	Rect(FRect),
 	Constraint(ConstraintSet)
 }
+
+impl Frame {
+	pub fn resolve(
+		&self,
+		id: AssetId,
+		parent: FRect,
+		siblings: &HashMap<AssetId, FRect>,
+	) -> Result<FRect, LayoutError> {
+		match self {
+			Frame::Rect(rect) => Ok(*rect),
+			Frame::Constraint(set) => set.resolve(id, parent, siblings),
+		}
+	}
+}
+
+/// Order in which `elements` have to be resolved so that every sibling an element refers to
+/// comes before it
+pub fn layout_order(elements: &[UiElement]) -> Result<Vec<usize>, LayoutError> {
+	let mut order = Vec::with_capacity(elements.len());
+	let mut resolved = vec![false; elements.len()];
+
+	while order.len() < elements.len() {
+		let mut progress = false;
+
+		for i in 0..elements.len() {
+			if resolved[i] {
+				continue;
+			}
+
+			let element = &elements[i];
+
+			let mut ready = true;
+
+			if let Frame::Constraint(set) = &element.frame {
+				for other_id in set.dependencies() {
+					let Some(j) = elements.iter().position(|e| e.id == other_id) else {
+						return Err(LayoutError::MissingElement { id: element.id, other_id });
+					};
+
+					ready &= resolved[j];
+				}
+			}
+
+			if ready {
+				resolved[i] = true;
+				order.push(i);
+				progress = true;
+			}
+		}
+
+		if !progress {
+			let ids = (0..elements.len())
+				.filter(|i| !resolved[*i])
+				.map(|i| elements[i].id)
+				.collect();
+
+			return Err(LayoutError::Cycle { ids });
+		}
+	}
+
+	Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use sdl3::render::FRect;
+	use crate::errors::LayoutError;
+	use crate::gui::constraints::{layout_order, Anchor, Constraint, ConstraintSet, Frame, Relation};
+	use crate::gui::UiElement;
+
+	fn constraint(own_anchor: Anchor, relation: Relation, constant: f32) -> Constraint {
+		Constraint {
+			own_anchor,
+			other_anchor: None,
+			other_id: None,
+			relation,
+			factor: 1.0,
+			constant,
+		}
+	}
+
+	fn element(id: i32, constraints: Vec<Constraint>) -> UiElement {
+		let json = serde_json::json!({
+			"id": id,
+			"frame": { "type": "Rect", "x": 0, "y": 0, "w": 0, "h": 0 },
+			"z": { "Foreground": 1 },
+			"bg": { "r": 0, "g": 0, "b": 0, "a": 0 },
+			"element_type": { "type": "Box" }
+		});
+
+		let mut element: UiElement = serde_json::from_value(json).unwrap();
+		element.frame = Frame::Constraint(ConstraintSet { constraints });
+
+		element
+	}
+
+	static PARENT: FRect = FRect {
+		x: 100.0,
+		y: 100.0,
+		w: 800.0,
+		h: 600.0,
+	};
+
+	#[test]
+	fn test_resolve_centered() {
+		let frame = Frame::Constraint(ConstraintSet {
+			constraints: vec![
+				constraint(Anchor::CenterHorizontally, Relation::Equal, 0.0),
+				constraint(Anchor::Width, Relation::None, 200.0),
+				constraint(Anchor::Top, Relation::Equal, 10.0),
+				constraint(Anchor::Bottom, Relation::Equal, -10.0),
+			],
+		});
+
+		let bounds = frame.resolve(1, PARENT, &HashMap::new()).unwrap();
+
+		assert_eq!(bounds, FRect { x: 300.0, y: 10.0, w: 200.0, h: 580.0 });
+	}
+
+	#[test]
+	fn test_resolve_sibling() {
+		let mut below = constraint(Anchor::Top, Relation::Equal, 10.0);
+		below.other_anchor = Some(Anchor::Bottom);
+		below.other_id = Some(2);
+
+		let frame = Frame::Constraint(ConstraintSet {
+			constraints: vec![
+				below,
+				constraint(Anchor::Height, Relation::None, 40.0),
+				constraint(Anchor::Leading, Relation::Equal, 0.0),
+				constraint(Anchor::Trailing, Relation::Equal, 0.0),
+				constraint(Anchor::Width, Relation::LessEqual, 1000.0),
+			],
+		});
+
+		let siblings = HashMap::from([(2, FRect { x: 0.0, y: 50.0, w: 10.0, h: 20.0 })]);
+
+		let bounds = frame.resolve(1, PARENT, &siblings).unwrap();
+
+		assert_eq!(bounds, FRect { x: 0.0, y: 80.0, w: 800.0, h: 40.0 });
+	}
+
+	#[test]
+	fn test_resolve_none_ignores_other_element() {
+		let mut width = constraint(Anchor::Width, Relation::None, 200.0);
+		width.other_id = Some(5);
+
+		let frame = Frame::Constraint(ConstraintSet {
+			constraints: vec![
+				constraint(Anchor::Leading, Relation::Equal, 0.0),
+				width,
+				constraint(Anchor::Top, Relation::Equal, 0.0),
+				constraint(Anchor::Height, Relation::None, 10.0),
+			],
+		});
+
+		let bounds = frame.resolve(1, PARENT, &HashMap::new()).unwrap();
+
+		assert_eq!(bounds, FRect { x: 0.0, y: 0.0, w: 200.0, h: 10.0 });
+	}
+
+	#[test]
+	fn test_resolve_errors() {
+		let underdetermined = Frame::Constraint(ConstraintSet {
+			constraints: vec![
+				constraint(Anchor::Leading, Relation::Equal, 0.0),
+				constraint(Anchor::Top, Relation::Equal, 0.0),
+				constraint(Anchor::Height, Relation::None, 10.0),
+			],
+		});
+
+		let unsatisfiable = Frame::Constraint(ConstraintSet {
+			constraints: vec![
+				constraint(Anchor::Leading, Relation::Equal, 0.0),
+				constraint(Anchor::Width, Relation::None, 100.0),
+				constraint(Anchor::Width, Relation::Greater, 100.0),
+				constraint(Anchor::Top, Relation::Equal, 0.0),
+				constraint(Anchor::Height, Relation::None, 10.0),
+			],
+		});
+
+		assert!(matches!(
+			underdetermined.resolve(1, PARENT, &HashMap::new()),
+			Err(LayoutError::Underdetermined { id: 1 })
+		));
+		assert!(matches!(
+			unsatisfiable.resolve(1, PARENT, &HashMap::new()),
+			Err(LayoutError::Unsatisfiable { id: 1 })
+		));
+	}
+
+	#[test]
+	fn test_layout_order() {
+		let mut after_3 = constraint(Anchor::Top, Relation::Equal, 0.0);
+		after_3.other_id = Some(3);
+		let mut after_2 = constraint(Anchor::Top, Relation::Equal, 0.0);
+		after_2.other_id = Some(2);
+
+		let elements = vec![element(2, vec![after_3]), element(3, vec![]), element(4, vec![after_2])];
+
+		assert_eq!(layout_order(&elements).unwrap(), vec![1, 0, 2]);
+	}
+
+	#[test]
+	fn test_layout_cycle() {
+		let mut after_3 = constraint(Anchor::Top, Relation::Equal, 0.0);
+		after_3.other_id = Some(3);
+		let mut after_2 = constraint(Anchor::Top, Relation::Equal, 0.0);
+		after_2.other_id = Some(2);
+
+		let elements = vec![element(2, vec![after_3]), element(3, vec![after_2]), element(4, vec![])];
+
+		assert!(matches!(layout_order(&elements), Err(LayoutError::Cycle { ids }) if ids == vec![2, 3]));
+	}
+
+	#[test]
+	fn test_layout_menu() {
+		let json = std::fs::read_to_string("./assets/gui/menu.json").unwrap();
+		let mut menu: UiElement = serde_json::from_str(&json).unwrap();
+
+		menu.layout(FRect { x: 0.0, y: 0.0, w: 800.0, h: 600.0 }).unwrap();
+
+		assert_eq!(menu.bounds, FRect { x: 0.0, y: 0.0, w: 800.0, h: 600.0 });
+		assert_eq!(menu.children[0].bounds, FRect { x: 300.0, y: 255.0, w: 200.0, h: 40.0 });
+		assert_eq!(menu.children[1].bounds, FRect { x: 300.0, y: 305.0, w: 200.0, h: 40.0 });
+	}
+}
@@ -5,10 +5,12 @@ use sdl3::pixels::Color;
 use sdl3::render::{FPoint, FRect};
 use serde::{Deserialize, Serialize};
 use crate::actions::Action;
+use crate::errors::LayoutError;
 use crate::game_object::drawable::DrawLayer;
+use crate::gui::constraints::{layout_order, Frame};
 use crate::math::bounds::Bounds;
 use crate::mouse::{Mouse, MouseButtonState};
-use crate::serialization::{AssetBounds, AssetColor, AssetId};
+use crate::serialization::{AssetColor, AssetId};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Handler {
@@ -18,6 +20,8 @@ pub enum Handler {
 	#[serde(with = "AssetColor")]
 	SetTextColor(Color),
 	SetText(String),
+	/// waits for the next key press and binds it to the action
+	Rebind(Action),
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
@@ -26,14 +30,14 @@ pub enum Event {
 	MouseUp,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum Align {
 	Start,
 	Center,
 	End
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub struct TextFormat {
 	pub font_id: AssetId,
 	#[serde(with = "AssetColor")]
@@ -57,7 +61,9 @@ pub enum ElementType {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct UiElement {
 	pub id: AssetId,
-	#[serde(with = "AssetBounds")]
+	pub frame: Frame,
+	/// resolved from `frame`, relative to the parent
+	#[serde(skip, default = "_default_bounds")]
 	pub bounds: FRect,
 	pub z: DrawLayer,
 	#[serde(with = "AssetColor")]
@@ -72,7 +78,42 @@ pub struct UiElement {
 	pub element_type: ElementType
 }
 
+fn _default_bounds() -> FRect {
+	FRect {
+		x: 0.0,
+		y: 0.0,
+		w: 0.0,
+		h: 0.0,
+	}
+}
+
 impl UiElement {
+	/// Resolves the frames of this element and all of its children; `parent` is the window for
+	/// a root element
+	pub fn layout(&mut self, parent: FRect) -> Result<(), LayoutError> {
+		self.bounds = self.frame.resolve(self.id, parent, &HashMap::new())?;
+
+		self.layout_children()
+	}
+
+	fn layout_children(&mut self) -> Result<(), LayoutError> {
+		let order = layout_order(&self.children)?;
+		let mut siblings = HashMap::with_capacity(self.children.len());
+
+		for i in order {
+			let child = &mut self.children[i];
+
+			child.bounds = child.frame.resolve(child.id, self.bounds, &siblings)?;
+			siblings.insert(child.id, child.bounds);
+		}
+
+		for i in 0..self.children.len() {
+			self.children[i].layout_children()?;
+		}
+
+		Ok(())
+	}
+
 	pub fn update_element(&mut self, element: UiElement) {
 		if self.id == element.id {
 			self.mouse = element.mouse;
@@ -197,7 +238,9 @@ impl UiElement {
 		elements.last().map(|e| *e)
 	}
 	
-	pub fn handle_event(&mut self, mouse: Mouse) -> Option<Action>
+	/// Applies the handlers of the element under the mouse; handlers that have to be executed by
+	/// the game (`Action`, `Rebind`) are returned
+	pub fn handle_event(&mut self, mouse: Mouse) -> Option<Handler>
 	{
 		let mut e;
 
@@ -222,13 +265,13 @@ impl UiElement {
 			return None;
 		};
 
-		let mut res_action = None;
+		let mut res_handler = None;
 
 		for i in 0..handlers.len() {
 			let handler = &handlers[i];
 
 			match (handler, &mut e.element_type) {
-				(Handler::Action(action), _) => { res_action = Some(*action) }
+				(Handler::Action(_) | Handler::Rebind(_), _) => { res_handler = Some(handler.clone()) }
 				(Handler::SetBackgroundColor(color), _) => { e.bg = *color }
 				(Handler::SetTextColor(color), ElementType::Label { format, .. }) => {
 					format.color = *color;
@@ -243,7 +286,7 @@ impl UiElement {
 		
 		self.update_element(e);
 
-		res_action
+		res_handler
 	}
 
 	fn get_event_type(old_state: &Mouse, new_state: &Mouse) -> Option<Event> {
@@ -0,0 +1,199 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use crate::cli::Arguments;
+use crate::errors::DataLoadError;
+use crate::game_assets::{load_game_data, load_levels};
+use crate::game_object::GameObject;
+use crate::game_object::world::World;
+use crate::input::InputState;
+use crate::replay::{Divergence, Recording};
+use crate::serialization::level::LevelData;
+use crate::serialization::script::ActionScript;
+use crate::settings::Settings;
+
+/// Steps the world of a level without a window, renderer or fonts
+pub struct HeadlessRunner {
+	world: World,
+	fixed_delta_t: f64,
+	tick_number: u64,
+}
+
+impl HeadlessRunner {
+	pub fn new(settings: &Settings, level: &LevelData, seed: u64) -> Self {
+		let mut world = World::new(settings.width as f32, settings.height as f32, seed);
+
+		world.load_level(level);
+
+		Self {
+			world,
+			fixed_delta_t: 1.0 / settings.tick_rate as f64,
+			tick_number: 0,
+		}
+	}
+
+	/// Advances the world by `ticks` fixed steps, taking the input for each step from `script`
+	pub fn run(&mut self, ticks: u64, script: &ActionScript) {
+		let no_input = InputState::default();
+
+		for _ in 0..ticks {
+			let input = script.input_at(self.tick_number).unwrap_or(&no_input);
+
+			self.tick(input);
+		}
+	}
+
+	pub fn tick(&mut self, input: &InputState) {
+		self.world.tick(self.fixed_delta_t, input);
+		self.tick_number += 1;
+	}
+
+	/// Feeds the recorded input into the world and stops at the first tick that differs
+	pub fn replay(&mut self, recording: &Recording) -> Result<(), Divergence> {
+		for i in 0..recording.ticks.len() {
+			self.tick(&recording.ticks[i].input);
+
+			recording.verify(i, self.game_objects())?;
+		}
+
+		Ok(())
+	}
+
+	pub fn game_objects(&self) -> &Vec<GameObject> {
+		self.world.get_game_objects()
+	}
+
+	pub fn to_json(&self) -> serde_json::Result<String> {
+		serde_json::to_string_pretty(self.game_objects())
+	}
+}
+
+/// Entry point for `--headless`; writes the final state of all game objects as json
+pub fn run_headless(
+	settings: &Settings,
+	arguments: &Arguments,
+	ticks: u64,
+	seed: u64,
+) -> Result<(), DataLoadError> {
+	let data_path = Path::new(&settings.asset_file);
+	let game_data = load_game_data(data_path)?;
+	let level_data = load_levels(&game_data);
+
+	let Some(level) = level_data.get(arguments.level) else {
+		return Err(DataLoadError {
+			path: data_path.to_path_buf(),
+			reason: format!("there is no level {}", arguments.level),
+		});
+	};
+
+	let script = match &arguments.script {
+		Some(path) => load_script(path)?,
+		None => ActionScript::default(),
+	};
+
+	let mut runner = HeadlessRunner::new(settings, level, seed);
+
+	runner.run(ticks, &script);
+
+	let json = runner.to_json().expect("could not serialize game objects");
+
+	match &arguments.output {
+		Some(path) => fs::write(path, json).map_err(|err| DataLoadError {
+			path: path.clone(),
+			reason: err.to_string(),
+		}),
+		None => {
+			println!("{json}");
+			Ok(())
+		}
+	}
+}
+
+/// Entry point for `--replay`; the level is loaded with the seed of the recording
+pub fn run_replay(settings: &Settings, path: &Path) -> Result<Result<(), Divergence>, DataLoadError> {
+	let recording = Recording::load(path)?;
+
+	let data_path = Path::new(&settings.asset_file);
+	let game_data = load_game_data(data_path)?;
+	let level_data = load_levels(&game_data);
+
+	let Some(level) = level_data.get(recording.level) else {
+		return Err(DataLoadError {
+			path: path.to_path_buf(),
+			reason: format!("there is no level {}", recording.level),
+		});
+	};
+
+	let settings = Settings {
+		tick_rate: recording.tick_rate,
+		..settings.clone()
+	};
+
+	let mut runner = HeadlessRunner::new(&settings, level, recording.seed);
+
+	Ok(runner.replay(&recording))
+}
+
+fn load_script(path: &Path) -> Result<ActionScript, DataLoadError> {
+	let file = File::open(path).map_err(|err| DataLoadError {
+		path: path.to_path_buf(),
+		reason: err.to_string(),
+	})?;
+
+	let reader = BufReader::new(file);
+
+	serde_json::from_reader(reader).map_err(|err| DataLoadError {
+		path: path.to_path_buf(),
+		reason: err.to_string(),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::actions::Action;
+	use crate::input::InputState;
+	use crate::replay::Recording;
+	use crate::math::bounds::Bounds;
+	use crate::test_level::{input, player_bounds, script, TestLevel};
+
+	// the smoke test of the runner; the behaviours are tested in their own modules
+	#[test]
+	fn test_script_moves_player() {
+		let mut runner = TestLevel::new().runner();
+		let script = script([(60, InputState::default()), (60, input([Action::MoveRight]))]);
+
+		runner.run(60, &script);
+		let before = player_bounds(&runner);
+
+		assert!(before.bottom() >= 580.0);
+		assert!(before.bottom() < 600.0);
+
+		runner.run(60, &script);
+		let after = player_bounds(&runner);
+
+		assert!(after.x > before.x);
+	}
+
+	#[test]
+	fn test_replay() {
+		let mut recording = Recording::new(0, 0, 60);
+		let mut recorder = TestLevel::new().runner();
+
+		for i in 0..120 {
+			let held = if i < 60 { input([Action::MoveRight, Action::Jump]) } else { input([]) };
+
+			recorder.tick(&held);
+			recording.record(&held, recorder.game_objects());
+		}
+
+		assert!(TestLevel::new().runner().replay(&recording).is_ok());
+
+		recording.ticks[90].bounds[1].bounds.x += 1.0;
+
+		let divergence = TestLevel::new().runner().replay(&recording).unwrap_err();
+
+		assert_eq!(divergence.tick, 90);
+		assert_eq!(divergence.id, recording.ticks[90].bounds[1].id);
+	}
+}
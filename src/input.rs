@@ -0,0 +1,163 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::actions::Action;
+use crate::mouse::Mouse;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum InputAxis {
+	MoveX,
+	MoveY,
+	AimX,
+	AimY,
+}
+
+impl InputAxis {
+	/// The actions that push the axis to -1.0 and 1.0
+	fn digital_actions(&self) -> Option<(Action, Action)> {
+		match self {
+			InputAxis::MoveX => Some((Action::MoveLeft, Action::MoveRight)),
+			InputAxis::MoveY => Some((Action::MoveUp, Action::MoveDown)),
+			InputAxis::AimX | InputAxis::AimY => None,
+		}
+	}
+}
+
+/// The device an action was pressed on
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum InputSource {
+	Keyboard,
+	/// the joystick id of the gamepad
+	Gamepad(u32),
+}
+
+/// Everything the player is pressing or pointing at during a tick
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct InputState {
+	pub actions: HashSet<Action>,
+	/// analog values in -1.0..=1.0; axes that are not set are centered
+	#[serde(default)]
+	pub axes: HashMap<InputAxis, f32>,
+	#[serde(default)]
+	pub mouse: Mouse,
+	/// the actions held on each device; replays only need the resulting `actions`
+	#[serde(skip)]
+	sources: HashMap<InputSource, HashSet<Action>>,
+}
+
+impl InputState {
+	pub fn pressed(&self, action: Action) -> bool {
+		self.actions.contains(&action)
+	}
+
+	/// Presses `action` on `source`; it stays in `actions` until every source released it
+	pub fn press(&mut self, source: InputSource, action: Action) {
+		self.sources.entry(source).or_default().insert(action);
+		self.actions.insert(action);
+	}
+
+	pub fn release(&mut self, source: InputSource, action: Action) {
+		if let Some(pressed) = self.sources.get_mut(&source) {
+			pressed.remove(&action);
+		}
+
+		if !self.sources.values().any(|pressed| pressed.contains(&action)) {
+			self.actions.remove(&action);
+		}
+	}
+
+	/// Releases everything held on `source`, e.g. when a gamepad gets unplugged
+	pub fn release_all(&mut self, source: InputSource) {
+		for action in self.sources.remove(&source).unwrap_or_default() {
+			self.release(source, action);
+		}
+	}
+
+	/// The value of an axis; pressed digital actions win over the analog value
+	pub fn axis(&self, axis: InputAxis) -> f32 {
+		if let Some((negative, positive)) = axis.digital_actions() {
+			match (self.pressed(negative), self.pressed(positive)) {
+				(true, false) => return -1.0,
+				(false, true) => return 1.0,
+				_ => {}
+			}
+		}
+
+		self.axes.get(&axis).copied().unwrap_or(0.0)
+	}
+
+	/// Sets a raw analog value; values inside the dead zone are centered and the remaining
+	/// range is scaled back to -1.0..=1.0
+	pub fn set_axis(&mut self, axis: InputAxis, value: f32, dead_zone: f32) {
+		let value = value.clamp(-1.0, 1.0);
+
+		let value = if value.abs() <= dead_zone {
+			0.0
+		} else {
+			value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+		};
+
+		self.axes.insert(axis, value);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::actions::Action;
+	use crate::input::{InputAxis, InputSource, InputState};
+
+	#[test]
+	fn test_sources() {
+		let mut input = InputState::default();
+
+		input.press(InputSource::Keyboard, Action::Jump);
+		input.press(InputSource::Gamepad(1), Action::Jump);
+		input.release(InputSource::Gamepad(1), Action::Jump);
+
+		assert!(input.pressed(Action::Jump));
+
+		input.press(InputSource::Gamepad(1), Action::Jump);
+		input.release(InputSource::Keyboard, Action::Jump);
+
+		assert!(input.pressed(Action::Jump));
+
+		input.press(InputSource::Gamepad(1), Action::Duck);
+		input.release_all(InputSource::Gamepad(1));
+
+		assert!(input.actions.is_empty());
+
+		// releasing a key that was never pressed doesn't touch the other sources
+		input.press(InputSource::Gamepad(2), Action::Sprint);
+		input.release(InputSource::Keyboard, Action::Sprint);
+
+		assert!(input.pressed(Action::Sprint));
+	}
+
+	#[test]
+	fn test_dead_zone() {
+		let mut input = InputState::default();
+
+		input.set_axis(InputAxis::MoveX, 0.1, 0.2);
+		assert_eq!(input.axis(InputAxis::MoveX), 0.0);
+
+		input.set_axis(InputAxis::MoveX, -0.6, 0.2);
+		assert!((input.axis(InputAxis::MoveX) + 0.5).abs() < 0.001);
+
+		input.set_axis(InputAxis::MoveX, 2.0, 0.2);
+		assert_eq!(input.axis(InputAxis::MoveX), 1.0);
+	}
+
+	#[test]
+	fn test_digital_wins() {
+		let mut input = InputState::default();
+
+		input.set_axis(InputAxis::MoveX, 0.5, 0.0);
+		input.actions.insert(Action::MoveLeft);
+
+		assert_eq!(input.axis(InputAxis::MoveX), -1.0);
+
+		input.actions.insert(Action::MoveRight);
+
+		assert_eq!(input.axis(InputAxis::MoveX), 0.5);
+		assert_eq!(input.axis(InputAxis::MoveY), 0.0);
+	}
+}
@@ -0,0 +1,21 @@
+mod serialization;
+mod gui;
+mod actions;
+mod mouse;
+mod errors;
+mod game_assets;
+mod texture_cache;
+mod gamepad;
+mod input;
+mod replay;
+mod render_batch;
+#[cfg(test)]
+mod test_level;
+
+pub mod game;
+pub mod game_object;
+pub mod math;
+pub mod util;
+pub mod settings;
+pub mod cli;
+pub mod headless;
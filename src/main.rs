@@ -1,50 +1,65 @@
-mod game;
-mod serialization;
-mod game_object;
-mod math;
-mod util;
-mod gui;
-mod actions;
-mod mouse;
-mod settings;
-mod errors;
-mod game_assets;
-
-use std::fs;
-use crate::game::Game;
-use crate::util::seed_random;
+use demo_game::game::Game;
 use sdl3::ttf;
 use std::path::{Path};
 use std::time::SystemTime;
-use crate::errors::DataLoadError;
-use crate::settings::Settings;
+use demo_game::settings::{Settings, SETTINGS_PATH};
+use demo_game::cli::Arguments;
+use demo_game::headless::{run_headless, run_replay};
 
 fn main() {
-    let now = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .expect("Could not get current time");
+	let settings_path = Path::new(SETTINGS_PATH);
+	let settings = Settings::load(settings_path).unwrap_or_else(|err| panic!("{err}"));
 
-    seed_random(now.as_secs() as u32);
+	let arguments = Arguments::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+		eprintln!("{err}");
+		std::process::exit(2);
+	});
 
-	let settings_path = Path::new("./settings.toml");
-	let settings = load_game_setting(settings_path).expect("Could not parse assets.json");
+	let seed = arguments.seed.unwrap_or_else(|| {
+		let now = SystemTime::now()
+			.duration_since(SystemTime::UNIX_EPOCH)
+			.expect("Could not get current time");
+
+		now.as_secs()
+	});
+
+	if let Some(path) = &arguments.replay {
+		match run_replay(&settings, path).unwrap_or_else(|err| panic!("{err}")) {
+			Ok(()) => println!("replay matches the recording"),
+			Err(divergence) => {
+				eprintln!("{divergence}");
+				std::process::exit(1);
+			}
+		}
+
+		return;
+	}
+
+	if let Some(ticks) = arguments.headless_ticks {
+		run_headless(&settings, &arguments, ticks, seed).expect("headless run failed");
+		return;
+	}
 
     let sdl_context = sdl3::init().unwrap();
 
 	let ttf_context = ttf::init();
     let ttf_context = ttf_context.expect("font context init error");
 
+    let canvas = Game::create_canvas(&settings, &sdl_context);
+    let texture_creator = canvas.texture_creator();
+
     let mut game: Game = Game::new(
 		settings,
         &sdl_context,
         &ttf_context,
+        canvas,
+        &texture_creator,
+        seed,
     );
 
-    game.run();
-}
+    if let Some(path) = arguments.record {
+        game.start_recording(path);
+    }
 
-fn load_game_setting(path: &Path) -> Result<Settings, DataLoadError> {
-	let data = fs::read_to_string(path).unwrap();
-
-	toml::de::from_str(&data).map_err(|_| DataLoadError { path: path.to_path_buf() })
+    game.run();
 }
@@ -33,6 +33,9 @@ pub trait Bounds {
 	fn intersection(&self, other: Self) -> Self;
 
 	fn join(&self, other: Self) -> Self;
+
+	/// Linear interpolation between `self` (t = 0.0) and `other` (t = 1.0)
+	fn lerp(&self, other: Self, t: f32) -> Self;
 }
 
 impl Bounds for FRect {
@@ -108,6 +111,15 @@ impl Bounds for FRect {
 			h,
 		}
 	}
+
+	fn lerp(&self, other: Self, t: f32) -> Self {
+		Self {
+			x: self.x + (other.x - self.x) * t,
+			y: self.y + (other.y - self.y) * t,
+			w: self.w + (other.w - self.w) * t,
+			h: self.h + (other.h - self.h) * t,
+		}
+	}
 }
 
 #[cfg(test)]
@@ -242,4 +254,32 @@ mod tests {
         assert!(canvas.intersects(r4));
         assert!(!canvas.intersects(r5));
     }
+
+    #[test]
+    fn test_lerp() {
+        let from = FRect {
+            x: 0.0,
+            y: 0.0,
+            w: 10.0,
+            h: 10.0,
+        };
+        let to = FRect {
+            x: 10.0,
+            y: -10.0,
+            w: 20.0,
+            h: 10.0,
+        };
+
+        assert_eq!(from.lerp(to, 0.0), from);
+        assert_eq!(from.lerp(to, 1.0), to);
+        assert_eq!(
+            from.lerp(to, 0.5),
+            FRect {
+                x: 5.0,
+                y: -5.0,
+                w: 15.0,
+                h: 10.0,
+            }
+        );
+    }
 }
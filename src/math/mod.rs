@@ -4,7 +4,9 @@ pub mod vector2;
 pub trait VectorOps {
     type Output;
 
-    fn len(&self) -> Self::Output;
+    fn length(&self) -> Self::Output;
 
     fn normal(&self) -> Self;
+
+    fn dot(&self, other: Self) -> Self::Output;
 }
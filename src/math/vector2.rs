@@ -48,24 +48,32 @@ impl Vector2<i64> {
 impl VectorOps for Vector2<f32> {
     type Output = f32;
 
-    fn len(&self) -> f32 {
+    fn length(&self) -> f32 {
         (self.x * self.x + self.y * self.y).sqrt()
     }
 
     fn normal(&self) -> Vector2<f32> {
-        *self / self.len()
+        *self / self.length()
+    }
+
+    fn dot(&self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y
     }
 }
 
 impl VectorOps for Vector2<f64> {
     type Output = f64;
 
-    fn len(&self) -> f64 {
+    fn length(&self) -> f64 {
         (self.x * self.x + self.y * self.y).sqrt()
     }
 
     fn normal(&self) -> Vector2<f64> {
-        *self / self.len()
+        *self / self.length()
+    }
+
+    fn dot(&self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y
     }
 }
 
@@ -243,7 +251,7 @@ mod tests {
 		let res1 = v1.normal();
 		let res2 = v2.normal();
 
-		assert_eq!(1.0, res1.len());
-		assert_eq!(1.0, res2.len());
+		assert_eq!(1.0, res1.length());
+		assert_eq!(1.0, res2.length());
 	}
 }
@@ -0,0 +1,110 @@
+use sdl3::pixels::{Color, FColor};
+use sdl3::render::{FPoint, FRect, Vertex, WindowCanvas};
+use crate::game_assets::GameAssets;
+use crate::serialization::AssetId;
+
+/// Textured rects that share a texture, drawn with a single geometry call; consecutive tiles of
+/// a tilemap layer end up in one batch
+#[derive(Default)]
+pub struct RenderBatch {
+	texture_id: Option<AssetId>,
+	vertices: Vec<Vertex>,
+	indices: Vec<u32>,
+}
+
+impl RenderBatch {
+	pub fn texture_id(&self) -> Option<AssetId> {
+		self.texture_id
+	}
+
+	/// Adds `rect` showing the `source` part of the texture, or all of it; the tint multiplies
+	/// the colors of the texture like a color mod
+	pub fn push(&mut self, texture_id: AssetId, texture_size: (f32, f32), source: Option<FRect>, rect: FRect, tint: Color) {
+		debug_assert!(self.texture_id.is_none() || self.texture_id == Some(texture_id));
+
+		self.texture_id = Some(texture_id);
+
+		let first = self.vertices.len() as u32;
+
+		self.vertices.extend(quad(rect, source, texture_size, tint));
+		self.indices.extend([first, first + 1, first + 2, first + 2, first + 3, first]);
+	}
+
+	/// Draws the batch with its texture and empties it
+	pub fn flush(&mut self, canvas: &mut WindowCanvas, assets: &GameAssets) {
+		let texture = self.texture_id.and_then(|id| assets.texture(id));
+
+		if !self.indices.is_empty() {
+			canvas
+				.render_geometry(&self.vertices, texture, self.indices.as_slice())
+				.expect("draw error");
+		}
+
+		self.texture_id = None;
+		self.vertices.clear();
+		self.indices.clear();
+	}
+}
+
+/// The corners of `rect` clockwise from the top left, with texture coordinates of `source`
+fn quad(rect: FRect, source: Option<FRect>, (width, height): (f32, f32), tint: Color) -> [Vertex; 4] {
+	let source = source.unwrap_or(FRect { x: 0.0, y: 0.0, w: width, h: height });
+
+	let left = source.x / width;
+	let top = source.y / height;
+	let right = (source.x + source.w) / width;
+	let bottom = (source.y + source.h) / height;
+
+	// like a color mod, the tint doesn't make the texture transparent
+	let color = FColor { a: 1.0, ..FColor::from(tint) };
+
+	let vertex = |x: f32, y: f32, u: f32, v: f32| Vertex {
+		position: FPoint::new(x, y),
+		color,
+		tex_coord: FPoint::new(u, v),
+	};
+
+	[
+		vertex(rect.x, rect.y, left, top),
+		vertex(rect.x + rect.w, rect.y, right, top),
+		vertex(rect.x + rect.w, rect.y + rect.h, right, bottom),
+		vertex(rect.x, rect.y + rect.h, left, bottom),
+	]
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::pixels::Color;
+	use sdl3::render::{FPoint, FRect};
+	use crate::render_batch::{quad, RenderBatch};
+
+	#[test]
+	fn test_quad_maps_the_source() {
+		let rect = FRect { x: 10.0, y: 20.0, w: 32.0, h: 32.0 };
+		let source = FRect { x: 32.0, y: 0.0, w: 32.0, h: 64.0 };
+		let vertices = quad(rect, Some(source), (128.0, 64.0), Color::WHITE);
+
+		assert_eq!(vertices[0].position, FPoint::new(10.0, 20.0));
+		assert_eq!(vertices[0].tex_coord, FPoint::new(0.25, 0.0));
+		assert_eq!(vertices[2].position, FPoint::new(42.0, 52.0));
+		assert_eq!(vertices[2].tex_coord, FPoint::new(0.5, 1.0));
+
+		let vertices = quad(rect, None, (128.0, 64.0), Color::RGBA(255, 0, 0, 0));
+
+		assert_eq!(vertices[3].tex_coord, FPoint::new(0.0, 1.0));
+		assert_eq!((vertices[3].color.r, vertices[3].color.g, vertices[3].color.a), (1.0, 0.0, 1.0));
+	}
+
+	#[test]
+	fn test_tiles_share_a_batch() {
+		let mut batch = RenderBatch::default();
+
+		for i in 0..3 {
+			batch.push(1, (64.0, 64.0), None, FRect { x: i as f32 * 32.0, y: 0.0, w: 32.0, h: 32.0 }, Color::WHITE);
+		}
+
+		assert_eq!(batch.texture_id(), Some(1));
+		assert_eq!(batch.vertices.len(), 12);
+		assert_eq!(&batch.indices[6..12], &[4, 5, 6, 6, 7, 4]);
+	}
+}
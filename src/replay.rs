@@ -0,0 +1,158 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use sdl3::render::FRect;
+use serde::{Deserialize, Serialize};
+use crate::errors::DataLoadError;
+use crate::game_object::GameObject;
+use crate::input::InputState;
+use crate::serialization::{AssetBounds, AssetId};
+use crate::settings::{_de_tick_rate, Fps};
+
+/// Allowed difference between recorded and replayed bounds
+static EPSILON: f32 = 0.001;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct RecordedBounds {
+	pub id: AssetId,
+	#[serde(with = "AssetBounds")]
+	pub bounds: FRect,
+}
+
+/// The input of a single tick and the bounds of all objects after it
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RecordedTick {
+	pub input: InputState,
+	pub bounds: Vec<RecordedBounds>,
+}
+
+/// Everything needed to reproduce a run of a level tick by tick
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Recording {
+	pub seed: u64,
+	pub level: usize,
+	#[serde(deserialize_with = "_de_tick_rate")]
+	pub tick_rate: Fps,
+	pub ticks: Vec<RecordedTick>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Divergence {
+	pub tick: usize,
+	pub id: AssetId,
+	pub expected: Option<FRect>,
+	pub actual: Option<FRect>,
+}
+
+impl Display for Divergence {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let tick = self.tick;
+		let id = self.id;
+		let expected = self.expected;
+		let actual = self.actual;
+		write!(f, "object {id} diverged in tick {tick}: expected {expected:?}, got {actual:?}")
+	}
+}
+
+impl Recording {
+	pub fn new(seed: u64, level: usize, tick_rate: Fps) -> Self {
+		Self {
+			seed,
+			level,
+			tick_rate,
+			ticks: Vec::new(),
+		}
+	}
+
+	/// Stores the input of a tick together with the objects it resulted in
+	pub fn record(&mut self, input: &InputState, game_objects: &[GameObject]) {
+		self.ticks.push(RecordedTick {
+			input: input.clone(),
+			bounds: record_bounds(game_objects),
+		});
+	}
+
+	/// Compares the objects after replaying `tick` with the recorded ones
+	pub fn verify(&self, tick: usize, game_objects: &[GameObject]) -> Result<(), Divergence> {
+		let recorded = &self.ticks[tick].bounds;
+		let actual = record_bounds(game_objects);
+
+		for i in 0..usize::max(recorded.len(), actual.len()) {
+			let expected = recorded.get(i);
+			let replayed = actual.get(i);
+
+			let matches = match (expected, replayed) {
+				(Some(e), Some(a)) => e.id == a.id && bounds_match(e.bounds, a.bounds),
+				_ => false,
+			};
+
+			if !matches {
+				return Err(Divergence {
+					tick,
+					id: expected.or(replayed).map(|b| b.id).unwrap_or_default(),
+					expected: expected.map(|b| b.bounds),
+					actual: replayed.map(|b| b.bounds),
+				});
+			}
+		}
+
+		Ok(())
+	}
+
+	pub fn load(path: &Path) -> Result<Self, DataLoadError> {
+		let data = fs::read_to_string(path).map_err(|err| DataLoadError {
+			path: path.to_path_buf(),
+			reason: err.to_string(),
+		})?;
+
+		serde_json::from_str(&data).map_err(|err| DataLoadError {
+			path: path.to_path_buf(),
+			reason: err.to_string(),
+		})
+	}
+
+	pub fn save(&self, path: &Path) -> Result<(), DataLoadError> {
+		let data = serde_json::to_string(self).map_err(|err| DataLoadError {
+			path: path.to_path_buf(),
+			reason: err.to_string(),
+		})?;
+
+		fs::write(path, data).map_err(|err| DataLoadError {
+			path: path.to_path_buf(),
+			reason: err.to_string(),
+		})
+	}
+}
+
+fn record_bounds(game_objects: &[GameObject]) -> Vec<RecordedBounds> {
+	game_objects
+		.iter()
+		.map(|o| RecordedBounds {
+			id: o.id,
+			bounds: o.bounds,
+		})
+		.collect()
+}
+
+fn bounds_match(a: FRect, b: FRect) -> bool {
+	(a.x - b.x).abs() <= EPSILON
+		&& (a.y - b.y).abs() <= EPSILON
+		&& (a.w - b.w).abs() <= EPSILON
+		&& (a.h - b.h).abs() <= EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::replay::Recording;
+
+	#[test]
+	fn test_zero_tick_rate_is_rejected() {
+		let recording = serde_json::to_string(&Recording::new(0, 0, 60)).unwrap();
+
+		assert!(serde_json::from_str::<Recording>(&recording).is_ok());
+
+		let recording = recording.replace(r#""tick_rate":60"#, r#""tick_rate":0"#);
+
+		assert!(serde_json::from_str::<Recording>(&recording).is_err());
+	}
+}
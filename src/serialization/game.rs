@@ -1,5 +1,6 @@
 use crate::serialization::AssetId;
 use crate::serialization::font::FontDefinition;
+use crate::serialization::sprite::SpriteSheetDefinition;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -11,6 +12,7 @@ pub struct AssetDefinition {
 pub type TextureDefinition = AssetDefinition;
 pub type LevelDefinition = AssetDefinition;
 pub type GuiDefinition = AssetDefinition;
+pub type PrefabDefinition = AssetDefinition;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GameData {
@@ -18,5 +20,9 @@ pub struct GameData {
     pub textures: Vec<TextureDefinition>,
     pub levels: Vec<LevelDefinition>,
 	pub guis: Vec<GuiDefinition>,
+	#[serde(default)]
+	pub prefabs: Vec<PrefabDefinition>,
+	#[serde(default)]
+	pub sprite_sheets: Vec<SpriteSheetDefinition>,
     pub debug_font_id: AssetId,
 }
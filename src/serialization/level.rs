@@ -1,12 +1,25 @@
+use std::collections::{HashMap, HashSet};
 use crate::serialization::{AssetBounds, AssetId, AssetPosition, AssetSize};
 use sdl3::render::{FPoint, FRect};
 use serde::{Deserialize, Serialize};
 use crate::game_object::GameObject;
+use crate::game_object::behaviour::animation::AnimationBehaviour;
+use crate::game_object::behaviour::controllable::ControllableBehaviour;
+use crate::game_object::camera::Camera;
+use crate::game_object::tilemap::Tilemap;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Player {
     pub texture_id: AssetId,
     pub size: AssetSize,
+    /// drawn instead of the texture if set
+    #[serde(default)]
+    pub sprite_sheet: Option<AssetId>,
+    #[serde(default)]
+    pub animation: Option<AnimationBehaviour>,
+    /// movement and jumping; the defaults if not set
+    #[serde(default)]
+    pub controllable: Option<ControllableBehaviour>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -18,4 +31,40 @@ pub struct LevelData {
     pub bounds: FRect,
     pub player: Player,
     pub objects: Vec<GameObject>,
+    /// objects that behaviours can spawn by name
+    #[serde(default)]
+    pub templates: HashMap<String, GameObject>,
+    #[serde(default)]
+    pub camera: Camera,
+    #[serde(default)]
+    pub tilemaps: Vec<Tilemap>,
+}
+
+impl LevelData {
+	/// The first object id that is used by more than one object
+	pub fn duplicate_id(&self) -> Option<i32> {
+		let mut ids = HashSet::new();
+
+		self.objects.iter().map(|o| o.id).find(|id| !ids.insert(*id))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_level::TestLevel;
+
+	static WALL: &str = r#"{
+		"id": 2,
+		"bounds": { "x": 0, "y": 0, "w": 20, "h": 580 },
+		"drawable": { "z": { "Foreground": 1 } },
+		"behaviours": [{ "type": "Collision" }]
+	}"#;
+
+	#[test]
+	fn test_duplicate_id() {
+		let level = TestLevel::new().object(WALL);
+
+		assert_eq!(level.level().duplicate_id(), None);
+		assert_eq!(level.object(WALL).level().duplicate_id(), Some(2));
+	}
 }
@@ -1,6 +1,9 @@
 pub mod font;
 pub mod game;
 pub mod level;
+pub mod prefab;
+pub mod script;
+pub mod sprite;
 
 use sdl3::render::FPoint;
 use sdl3::pixels::Color;
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use serde_json::Value;
+use crate::serialization::AssetId;
+
+/// Parsed prefab files by id; kept as json so that any field can be overridden
+pub type Prefabs = HashMap<AssetId, Value>;
+
+/// Field of a level object that names the prefab it is based on
+static PREFAB_KEY: &str = "prefab";
+
+/// Merges `overrides` into `base`: objects are merged by key, arrays by index and everything
+/// else is replaced
+pub fn merge(base: &mut Value, overrides: Value) {
+	match (base, overrides) {
+		(Value::Object(base), Value::Object(overrides)) => {
+			for (key, value) in overrides {
+				match base.get_mut(&key) {
+					Some(existing) => merge(existing, value),
+					None => {
+						base.insert(key, value);
+					}
+				}
+			}
+		}
+		(Value::Array(base), Value::Array(overrides)) => {
+			for (i, value) in overrides.into_iter().enumerate() {
+				match base.get_mut(i) {
+					Some(existing) => merge(existing, value),
+					None => base.push(value),
+				}
+			}
+		}
+		(base, overrides) => *base = overrides,
+	}
+}
+
+/// Replaces every object of a level that references a prefab with the prefab merged with the
+/// fields of the object; this applies to the objects and templates of the level
+pub fn apply_prefabs(level: &mut Value, prefabs: &Prefabs) -> Result<(), String> {
+	if let Some(Value::Array(objects)) = level.get_mut("objects") {
+		for object in objects {
+			apply_prefab(object, prefabs)?;
+		}
+	}
+
+	if let Some(Value::Object(templates)) = level.get_mut("templates") {
+		for (_, template) in templates {
+			apply_prefab(template, prefabs)?;
+		}
+	}
+
+	Ok(())
+}
+
+fn apply_prefab(object: &mut Value, prefabs: &Prefabs) -> Result<(), String> {
+	let Some(reference) = object.as_object_mut().and_then(|fields| fields.remove(PREFAB_KEY)) else {
+		return Ok(());
+	};
+
+	let id = reference
+		.as_i64()
+		.ok_or_else(|| format!("prefab reference {reference} is not an id"))?;
+
+	let mut merged = prefabs
+		.get(&(id as AssetId))
+		.ok_or_else(|| format!("there is no prefab {id}"))?
+		.clone();
+
+	merge(&mut merged, object.take());
+
+	*object = merged;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+	use crate::game_object::behaviour::BehaviourType;
+	use crate::serialization::level::LevelData;
+	use crate::serialization::prefab::{apply_prefabs, merge, Prefabs};
+
+	#[test]
+	fn test_merge() {
+		let mut base = json!({
+			"drawable": { "z": { "Foreground": 1 }, "texture_id": 1 },
+			"behaviours": [{ "type": "Dvd", "speed": { "type": "Fixed", "x": 1, "y": 1 } }, { "type": "Collision" }]
+		});
+
+		merge(&mut base, json!({
+			"id": 3,
+			"drawable": { "texture_id": 2 },
+			"behaviours": [{ "speed": { "x": 5 } }]
+		}));
+
+		assert_eq!(base, json!({
+			"id": 3,
+			"drawable": { "z": { "Foreground": 1 }, "texture_id": 2 },
+			"behaviours": [{ "type": "Dvd", "speed": { "type": "Fixed", "x": 5, "y": 1 } }, { "type": "Collision" }]
+		}));
+	}
+
+	#[test]
+	fn test_apply_prefabs() {
+		let prefabs = Prefabs::from([(1, json!({
+			"bounds": { "x": 0, "y": 0, "w": 100, "h": 20 },
+			"drawable": { "z": { "Foreground": 1 } },
+			"behaviours": [{ "type": "Collision" }]
+		}))]);
+
+		let mut level = json!({
+			"name": "Prefabs",
+			"start": { "x": 0, "y": 0 },
+			"player": { "texture_id": 4, "size": { "w": 64, "h": 64 } },
+			"bounds": { "x": 0, "y": 0, "w": 800, "h": 600 },
+			"objects": [
+				{ "id": 1, "prefab": 1, "bounds": { "x": 50, "y": 300 } },
+				{ "id": 2, "bounds": { "x": 0, "y": 0, "w": 1, "h": 1 }, "drawable": null, "behaviours": [] }
+			],
+			"templates": { "platform": { "prefab": 1 } }
+		});
+
+		apply_prefabs(&mut level, &prefabs).unwrap();
+
+		let level: LevelData = serde_json::from_value(level).unwrap();
+		let platform = &level.objects[0];
+
+		assert_eq!((platform.bounds.x, platform.bounds.y, platform.bounds.w), (50.0, 300.0, 100.0));
+		assert!(matches!(platform.behaviours[..], [BehaviourType::Collision(_)]));
+		assert!(level.objects[1].behaviours.is_empty());
+		assert_eq!(level.templates["platform"].bounds.w, 100.0);
+
+		let mut missing = json!({ "objects": [{ "prefab": 2 }] });
+
+		assert!(apply_prefabs(&mut missing, &prefabs).is_err());
+	}
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use crate::input::InputState;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ScriptStep {
+	pub ticks: u64,
+	#[serde(flatten)]
+	pub input: InputState,
+}
+
+/// A sequence of inputs, each held for a number of simulation ticks
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ActionScript {
+	pub steps: Vec<ScriptStep>,
+}
+
+impl ActionScript {
+	/// The input active at `tick`; `None` once the script has ended
+	pub fn input_at(&self, tick: u64) -> Option<&InputState> {
+		let mut start = 0;
+
+		for i in 0..self.steps.len() {
+			let step = &self.steps[i];
+
+			if tick < start + step.ticks {
+				return Some(&step.input);
+			}
+
+			start += step.ticks;
+		}
+
+		None
+	}
+}
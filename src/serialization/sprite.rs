@@ -0,0 +1,71 @@
+use sdl3::render::FRect;
+use serde::{Deserialize, Serialize};
+use crate::serialization::{AssetBounds, AssetId};
+
+/// Frames of the same size laid out left to right and top to bottom, starting at the top left
+/// corner of the texture
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct SpriteGrid {
+	pub w: f32,
+	pub h: f32,
+	pub columns: u32,
+	pub count: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(transparent)]
+pub struct SpriteFrame(#[serde(with = "AssetBounds")] pub FRect);
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SpriteSheetDefinition {
+	pub id: AssetId,
+	pub texture_id: AssetId,
+	#[serde(default)]
+	pub grid: Option<SpriteGrid>,
+	/// source rects of frames that don't fit the grid; they are numbered after the frames of the grid
+	#[serde(default)]
+	pub frames: Vec<SpriteFrame>,
+}
+
+impl SpriteSheetDefinition {
+	/// The part of the texture that shows the frame
+	pub fn frame(&self, index: usize) -> Option<FRect> {
+		let grid_count = self.grid.map_or(0, |grid| grid.count as usize);
+
+		if let Some(grid) = self.grid
+			&& index < grid_count
+		{
+			let columns = grid.columns.max(1) as usize;
+
+			return Some(FRect {
+				x: (index % columns) as f32 * grid.w,
+				y: (index / columns) as f32 * grid.h,
+				w: grid.w,
+				h: grid.h,
+			});
+		}
+
+		self.frames.get(index - grid_count).map(|frame| frame.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl3::render::FRect;
+	use crate::serialization::sprite::SpriteSheetDefinition;
+
+	#[test]
+	fn test_frames() {
+		let sheet: SpriteSheetDefinition = serde_json::from_str(r#"{
+			"id": 1,
+			"texture_id": 4,
+			"grid": { "w": 32, "h": 48, "columns": 4, "count": 6 },
+			"frames": [{ "x": 0, "y": 96, "w": 64, "h": 48 }]
+		}"#).unwrap();
+
+		assert_eq!(sheet.frame(0), Some(FRect { x: 0.0, y: 0.0, w: 32.0, h: 48.0 }));
+		assert_eq!(sheet.frame(5), Some(FRect { x: 32.0, y: 48.0, w: 32.0, h: 48.0 }));
+		assert_eq!(sheet.frame(6), Some(FRect { x: 0.0, y: 96.0, w: 64.0, h: 48.0 }));
+		assert_eq!(sheet.frame(7), None);
+	}
+}
@@ -1,11 +1,19 @@
 use crate::actions::{Action};
+use crate::errors::DataLoadError;
+use sdl3::gamepad::Button;
 use sdl3::keyboard::Keycode;
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 use std::fmt::Formatter;
+use std::hash::Hash;
+use std::fs;
 use std::marker::PhantomData;
-use serde::de::{MapAccess, Visitor};
+use std::path::Path;
+use serde::de::{Error, MapAccess, Unexpected, Visitor};
+use toml_edit::{value, DocumentMut, Item, Table, TomlError};
+
+pub static SETTINGS_PATH: &str = "./settings.toml";
 
 pub type Pixels = u16;
 pub type Fps = u16;
@@ -16,20 +24,134 @@ pub struct Settings {
     pub height: Pixels,
     pub frame_limit_active: bool,
     pub frame_limit: Fps,
+    /// ticks per second of the fixed update step; never 0
+    #[serde(default = "_default_tick_rate", deserialize_with = "_de_tick_rate")]
+    pub tick_rate: Fps,
     pub asset_file: String,
+    /// analog stick deflection below which a stick counts as centered
+    #[serde(default = "_default_dead_zone")]
+    pub dead_zone: f32,
     #[serde(
         default,
-        deserialize_with = "_de_key_code_map",
-        serialize_with = "_ser_key_code_map"
+        deserialize_with = "_de_input_map",
+        serialize_with = "_ser_input_map"
     )]
     pub keymap: HashMap<Keycode, Action>,
+    #[serde(
+        default,
+        deserialize_with = "_de_input_map",
+        serialize_with = "_ser_input_map"
+    )]
+    pub gamepad_map: HashMap<Button, Action>,
 }
 
-struct KeyMapVisitor {
-	marker: PhantomData<fn() -> HashMap<Keycode, Action>>
+impl Settings {
+	pub fn load(path: &Path) -> Result<Self, DataLoadError> {
+		let data = fs::read_to_string(path).map_err(|err| DataLoadError {
+			path: path.to_path_buf(),
+			reason: err.to_string(),
+		})?;
+
+		toml::de::from_str(&data).map_err(|err| DataLoadError {
+			path: path.to_path_buf(),
+			reason: err.to_string(),
+		})
+	}
+
+	/// Writes the keymap to the `[keymap]` table of the settings file, leaving the rest of the
+	/// file and its comments as they are
+	pub fn save_keymap(&self, path: &Path) -> Result<(), DataLoadError> {
+		let error = |reason: String| DataLoadError {
+			path: path.to_path_buf(),
+			reason,
+		};
+
+		let data = fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+		let mut document: DocumentMut = data.parse().map_err(|err: TomlError| error(err.to_string()))?;
+
+		let mut bindings: Vec<(String, Action)> = self.keymap.iter().map(|(keycode, action)| (keycode.name(), *action)).collect();
+		let mut keymap = Table::new();
+
+		bindings.sort_by(|a, b| a.0.cmp(&b.0));
+
+		for (name, action) in bindings {
+			let action = toml::Value::try_from(action).map_err(|err| error(err.to_string()))?;
+
+			keymap.insert(&name, value(action.as_str().unwrap_or_default()));
+		}
+
+		document.insert("keymap", Item::Table(keymap));
+
+		fs::write(path, document.to_string()).map_err(|err| error(err.to_string()))
+	}
+
+	/// Binds `action` to `keycode` only, replacing the keys it was bound to before
+	pub fn rebind(&mut self, keycode: Keycode, action: Action) {
+		self.keymap.retain(|_, a| *a != action);
+		self.keymap.insert(keycode, action);
+	}
+}
+
+fn _default_tick_rate() -> Fps {
+	60
+}
+
+fn _default_dead_zone() -> f32 {
+	0.15
+}
+
+/// Rejects a tick rate of 0, the fixed step of `1.0 / tick_rate` would be infinite
+pub fn _de_tick_rate<'de, D>(deserializer: D) -> Result<Fps, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let tick_rate = Fps::deserialize(deserializer)?;
+
+	if tick_rate == 0 {
+		return Err(D::Error::invalid_value(Unexpected::Unsigned(0), &"a tick rate of at least 1"));
+	}
+
+	Ok(tick_rate)
+}
+
+/// Inputs that are stored in the settings by their SDL name
+pub trait NamedInput: Sized + Eq + Hash {
+	const EXPECTED: &'static str;
+
+	fn from_name(name: &str) -> Option<Self>;
+
+	fn name(&self) -> String;
 }
 
-impl KeyMapVisitor {
+impl NamedInput for Keycode {
+	const EXPECTED: &'static str = "a SDL key name";
+
+	fn from_name(name: &str) -> Option<Self> {
+		Keycode::from_name(name)
+	}
+
+	fn name(&self) -> String {
+		Keycode::name(*self)
+	}
+}
+
+impl NamedInput for Button {
+	const EXPECTED: &'static str = "a SDL gamepad button name";
+
+	fn from_name(name: &str) -> Option<Self> {
+		Button::from_string(name)
+	}
+
+	fn name(&self) -> String {
+		self.string()
+	}
+}
+
+struct InputMapVisitor<K> {
+	marker: PhantomData<fn() -> HashMap<K, Action>>
+}
+
+impl<K> InputMapVisitor<K> {
 	fn new() -> Self {
 		Self {
 			marker: PhantomData
@@ -37,11 +159,11 @@ impl KeyMapVisitor {
 	}
 }
 
-impl<'de> Visitor<'de> for KeyMapVisitor {
-	type Value = HashMap<Keycode, Action>;
+impl<'de, K: NamedInput> Visitor<'de> for InputMapVisitor<K> {
+	type Value = HashMap<K, Action>;
 
 	fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-		formatter.write_str("didn't expect that")
+		write!(formatter, "a map of {} to actions", K::EXPECTED)
 	}
 
 	fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
@@ -50,41 +172,111 @@ impl<'de> Visitor<'de> for KeyMapVisitor {
 	{
 		let mut map = HashMap::with_capacity(access.size_hint().unwrap_or(0));
 
-		while let Some((key, value)) = access.next_entry()? {
-			let Some(keycode) = Keycode::from_name(key) else {
+		while let Some((key, value)) = access.next_entry::<String, Action>()? {
+			// a typo in one binding shouldn't lose all the others
+			let Some(input) = K::from_name(&key) else {
+				eprintln!("skipping '{key}' in the input map, it is not {}", K::EXPECTED);
 				continue;
 			};
 
-			map.insert(keycode, value);
+			map.insert(input, value);
 		}
 
 		Ok(map)
 	}
 }
 
-fn _de_key_code_map<'de, D>(deserializer: D) -> Result<HashMap<Keycode, Action>, D::Error>
+fn _de_input_map<'de, D, K>(deserializer: D) -> Result<HashMap<K, Action>, D::Error>
 where
     D: Deserializer<'de>,
+    K: NamedInput,
 {
-	let visitor = KeyMapVisitor::new();
+	let visitor = InputMapVisitor::new();
 
 	deserializer.deserialize_map(visitor)
 }
 
-fn _ser_key_code_map<S>(map: &HashMap<Keycode, Action>, s: S) -> Result<S::Ok, S::Error>
+fn _ser_input_map<S, K>(map: &HashMap<K, Action>, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
+    K: NamedInput,
 {
     let mut ser = s
         .serialize_map(Some(map.len()))
-        .expect("error while serializing key_code_map");
+        .expect("error while serializing input map");
 
-    map.iter().for_each(|(code, action)| {
-		let key = code.to_string();
+    map.iter().for_each(|(input, action)| {
+		let key = input.name();
 
         ser.serialize_entry(&key, action)
-            .expect("error while serializing key")
+            .expect("error while serializing input")
     });
 
     ser.end()
 }
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use sdl3::keyboard::Keycode;
+	use crate::actions::Action;
+	use crate::settings::Settings;
+
+	static SETTINGS: &str = r#"# the window size
+width = 800
+height = 600
+frame_limit_active = true
+frame_limit = 60
+asset_file = "./assets/assets.json"
+
+#[gamepad_map]
+#south = "Jump"
+"#;
+
+	#[test]
+	fn test_zero_tick_rate_is_rejected() {
+		let settings = |tick_rate: u16| format!(r#"
+			width = 800
+			height = 600
+			frame_limit_active = true
+			frame_limit = 60
+			tick_rate = {tick_rate}
+			asset_file = "./assets/assets.json"
+		"#);
+
+		assert!(toml::de::from_str::<Settings>(&settings(60)).is_ok());
+		assert!(toml::de::from_str::<Settings>(&settings(0)).is_err());
+	}
+
+	#[test]
+	fn test_unknown_key_is_skipped() {
+		let settings: Settings = toml::de::from_str(&format!("{SETTINGS}\n[keymap]\nSpace = \"Jump\"\nNoSuchKey = \"Duck\"\n")).unwrap();
+
+		assert_eq!(settings.keymap.len(), 1);
+		assert_eq!(settings.keymap.get(&Keycode::Space), Some(&Action::Jump));
+	}
+
+	#[test]
+	fn test_save_keymap_keeps_the_rest_of_the_file() {
+		let path = std::env::temp_dir().join(format!("demo-game-settings-{}.toml", std::process::id()));
+
+		fs::write(&path, SETTINGS).unwrap();
+
+		let mut settings = Settings::load(&path).unwrap();
+
+		settings.rebind(Keycode::Space, Action::Jump);
+		settings.save_keymap(&path).unwrap();
+		settings.rebind(Keycode::W, Action::Jump);
+		settings.save_keymap(&path).unwrap();
+
+		let data = fs::read_to_string(&path).unwrap();
+		let saved = Settings::load(&path).unwrap();
+
+		fs::remove_file(&path).unwrap();
+
+		assert!(data.starts_with("# the window size\n"));
+		assert!(data.contains("#south = \"Jump\""));
+		assert_eq!(saved.keymap.len(), 1);
+		assert_eq!(saved.keymap.get(&Keycode::W), Some(&Action::Jump));
+	}
+}
@@ -0,0 +1,114 @@
+use sdl3::render::FRect;
+use crate::actions::Action;
+use crate::headless::HeadlessRunner;
+use crate::input::InputState;
+use crate::serialization::level::LevelData;
+use crate::serialization::script::{ActionScript, ScriptStep};
+use crate::settings::{Fps, Settings};
+
+static SETTINGS: &str = r#"
+	width = 800
+	height = 600
+	frame_limit_active = true
+	frame_limit = 60
+	tick_rate = 60
+	asset_file = "./assets/assets.json"
+"#;
+
+static LEVEL: &str = r#"{
+	"name": "Test-Level",
+	"start": { "x": 100, "y": 400 },
+	"player": { "texture_id": 4, "size": { "w": 64, "h": 64 } },
+	"bounds": { "x": 0, "y": 0, "w": 800, "h": 600 },
+	"objects": [
+		{
+			"id": 1,
+			"bounds": { "x": 0, "y": 580, "w": 800, "h": 20 },
+			"drawable": { "z": { "Foreground": 1 } },
+			"behaviours": [{ "type": "Collision" }]
+		}
+	]
+}"#;
+
+/// The level the tests step through: an 800 x 600 room with the ground at y = 580 and a 64 x 64
+/// player starting at (100, 400), at 60 ticks per second
+pub struct TestLevel {
+	settings: Settings,
+	level: LevelData,
+}
+
+impl TestLevel {
+	pub fn new() -> Self {
+		Self {
+			settings: toml::de::from_str(SETTINGS).unwrap(),
+			level: serde_json::from_str(LEVEL).unwrap(),
+		}
+	}
+
+	pub fn start_y(mut self, y: f32) -> Self {
+		self.level.start.y = y;
+		self
+	}
+
+	pub fn tick_rate(mut self, tick_rate: Fps) -> Self {
+		self.settings.tick_rate = tick_rate;
+		self
+	}
+
+	/// Adds an object given as level json
+	pub fn object(mut self, json: &str) -> Self {
+		self.level.objects.push(serde_json::from_str(json).unwrap());
+		self
+	}
+
+	pub fn template(mut self, name: &str, json: &str) -> Self {
+		self.level.templates.insert(name.to_string(), serde_json::from_str(json).unwrap());
+		self
+	}
+
+	/// Replaces the controllable behaviour of the player
+	pub fn controllable(mut self, json: &str) -> Self {
+		self.level.player.controllable = Some(serde_json::from_str(json).unwrap());
+		self
+	}
+
+	pub fn animation(mut self, json: &str) -> Self {
+		self.level.player.animation = Some(serde_json::from_str(json).unwrap());
+		self
+	}
+
+	pub fn level(&self) -> &LevelData {
+		&self.level
+	}
+
+	pub fn runner(&self) -> HeadlessRunner {
+		HeadlessRunner::new(&self.settings, &self.level, 0)
+	}
+}
+
+pub fn input<const N: usize>(actions: [Action; N]) -> InputState {
+	let mut input = InputState::default();
+
+	input.actions = actions.into();
+	input
+}
+
+/// Each input is held for the given number of ticks, counted from the first tick of the runner
+pub fn script<const N: usize>(steps: [(u64, InputState); N]) -> ActionScript {
+	ActionScript {
+		steps: steps.into_iter().map(|(ticks, input)| ScriptStep { ticks, input }).collect(),
+	}
+}
+
+pub fn player_bounds(runner: &HeadlessRunner) -> FRect {
+	object_bounds(runner, -1)
+}
+
+pub fn object_bounds(runner: &HeadlessRunner, id: i32) -> FRect {
+	runner
+		.game_objects()
+		.iter()
+		.find(|o| o.id == id)
+		.unwrap_or_else(|| panic!("no object {id}"))
+		.bounds
+}
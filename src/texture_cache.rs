@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use sdl3::render::{Texture, TextureCreator};
+use sdl3::surface::Surface;
+use sdl3::ttf::Font;
+use sdl3::video::WindowContext;
+use crate::gui::TextFormat;
+use crate::serialization::AssetId;
+
+pub type TextKey = (String, TextFormat);
+
+struct CachedText<'a> {
+	texture: Texture<'a>,
+	used: bool,
+}
+
+/// Textures uploaded to a renderer once and reused every frame
+pub struct TextureCache<'a> {
+	texture_creator: &'a TextureCreator<WindowContext>,
+	textures: HashMap<AssetId, Texture<'a>>,
+	texts: HashMap<TextKey, CachedText<'a>>,
+}
+
+impl<'a> TextureCache<'a> {
+	pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+		Self {
+			texture_creator,
+			textures: HashMap::new(),
+			texts: HashMap::new(),
+		}
+	}
+
+	/// Uploads all surfaces, replacing whatever was cached before
+	pub fn load_textures(&mut self, surfaces: &HashMap<AssetId, Surface>) {
+		self.clear();
+
+		for (id, surface) in surfaces {
+			let texture = self
+				.texture_creator
+				.create_texture_from_surface(surface)
+				.expect("texture creation panic");
+
+			self.textures.insert(*id, texture);
+		}
+	}
+
+	pub fn texture(&self, id: AssetId) -> Option<&Texture<'a>> {
+		self.textures.get(&id)
+	}
+
+	/// The texture for `text`; only rasterised if it was not used in the previous frame
+	pub fn text(
+		&mut self,
+		text: &str,
+		format: &TextFormat,
+		fonts: &HashMap<AssetId, Font>,
+	) -> &Texture<'a> {
+		let key = (text.to_owned(), *format);
+
+		let cached = self.texts.entry(key).or_insert_with(|| {
+			let font = fonts.get(&format.font_id).expect("Invalid font id");
+			let surface = font
+				.render(text)
+				.blended(format.color)
+				.expect("text render panic");
+			let texture = self
+				.texture_creator
+				.create_texture_from_surface(surface)
+				.expect("texture creation panic");
+
+			CachedText {
+				texture,
+				used: false,
+			}
+		});
+
+		cached.used = true;
+
+		&cached.texture
+	}
+
+	/// Drops every text that was not drawn since the last call, e.g. outdated debug values
+	pub fn end_frame(&mut self) {
+		self.texts.retain(|_, cached| cached.used);
+
+		for cached in self.texts.values_mut() {
+			cached.used = false;
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.textures.clear();
+		self.texts.clear();
+	}
+}
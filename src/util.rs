@@ -1,11 +1,75 @@
-use sdl3::libc::{RAND_MAX, rand, srand};
+use serde::{Deserialize, Serialize};
 
-pub fn seed_random(seed: u32) {
-    unsafe {
-        srand(seed);
-    }
+/// Seedable random number generator (SplitMix64); produces the same sequence on every platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Random {
+	state: u64,
 }
 
-pub fn random(min: f32, max: f32) -> f32 {
-    unsafe { rand() as f32 / RAND_MAX as f32 * (max - min) + min }
+impl Random {
+	pub fn new(seed: u64) -> Self {
+		Self { state: seed }
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+		z ^ (z >> 31)
+	}
+
+	/// Uniformly distributed in `0.0..1.0`
+	pub fn next_f32(&mut self) -> f32 {
+		// the upper 24 bits are exactly representable as f32
+		(self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+	}
+
+	/// Uniformly distributed in `min..max`
+	pub fn range(&mut self, min: f32, max: f32) -> f32 {
+		self.next_f32() * (max - min) + min
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::util::Random;
+
+	#[test]
+	fn test_sequence_is_reproducible() {
+		let mut a = Random::new(42);
+		let mut b = Random::new(42);
+		let mut c = Random::new(43);
+
+		let seq_a: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+		let seq_b: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+		let seq_c: Vec<u64> = (0..8).map(|_| c.next_u64()).collect();
+
+		assert_eq!(seq_a, seq_b);
+		assert_ne!(seq_a, seq_c);
+	}
+
+	#[test]
+	fn test_range() {
+		let mut random = Random::new(7);
+
+		for _ in 0..1000 {
+			let value = random.range(-2.0, 3.0);
+
+			assert!((-2.0..3.0).contains(&value));
+		}
+	}
+
+	#[test]
+	fn test_state_roundtrip() {
+		let mut random = Random::new(1);
+		random.next_u64();
+
+		let json = serde_json::to_string(&random).unwrap();
+		let mut restored: Random = serde_json::from_str(&json).unwrap();
+
+		assert_eq!(random.next_u64(), restored.next_u64());
+	}
 }